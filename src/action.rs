@@ -0,0 +1,607 @@
+use anyhow::{anyhow, Context};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::time::{Duration, SystemTime};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::process::Child;
+
+use crate::{format_period, Clock};
+
+// How long a program that ran out of time gets to exit after SIGTERM before it is
+// killed
+const KILL_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+/// The program a task starts each time it runs.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Action {
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Directory the program starts in. `None` uses the app's own.
+    #[serde(default)]
+    pub working_dir: Option<PathBuf>,
+    /// Variables set on top of the app's environment.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Longest the program may run. It is then stopped along with every process it
+    /// started, and the run counts as timed out.
+    #[serde(default)]
+    pub timeout: Option<Duration>,
+}
+
+impl Action {
+    /// Splits a command line such as `backup.sh --dest "/mnt/My Backups"` into the
+    /// program and its arguments. Returns `None` for a blank line.
+    pub fn from_command_line(line: &str) -> Result<Option<Self>, anyhow::Error> {
+        let mut words = split_words(line)?.into_iter();
+        Ok(words.next().map(|program| Self {
+            program,
+            args: words.collect(),
+            ..Self::default()
+        }))
+    }
+
+    /// Starts the program and waits for it to exit, reading the start and end
+    /// times from `clock`. With `capture`, its stdout and stderr are saved to files;
+    /// otherwise they are discarded.
+    pub async fn run(&self, clock: &dyn Clock, capture: Option<&OutputCapture>) -> RunRecord {
+        self.run_with_grace(clock, capture, KILL_GRACE_PERIOD).await
+    }
+
+    async fn run_with_grace(
+        &self,
+        clock: &dyn Clock,
+        capture: Option<&OutputCapture>,
+        grace: Duration,
+    ) -> RunRecord {
+        let output = || match capture {
+            Some(_) => Stdio::piped(),
+            None => Stdio::null(),
+        };
+        let mut command = tokio::process::Command::new(&self.program);
+        command
+            .args(&self.args)
+            .envs(&self.env)
+            .stdin(Stdio::null())
+            .stdout(output())
+            .stderr(output())
+            .kill_on_drop(true);
+        if let Some(dir) = &self.working_dir {
+            command.current_dir(dir);
+        }
+        // Its own process group, so a timeout stops whatever it started as well
+        #[cfg(unix)]
+        command.process_group(0);
+
+        let started = clock.now();
+        let mut run = RunRecord {
+            started,
+            finished: started,
+            status: RunStatus::Failed(String::new()),
+            stdout: None,
+            stderr: None,
+            output_error: None,
+        };
+        let mut child = match command.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                run.status =
+                    RunStatus::Failed(format!("could not start '{}': {}", self.program, e));
+                run.finished = clock.now();
+                return run;
+            }
+        };
+
+        let (stdout, stderr) = match capture {
+            Some(capture) => {
+                let stem = capture.file_stem(started, child.id().unwrap_or_default());
+                (
+                    Some(stem.with_extension("stdout.log")),
+                    Some(stem.with_extension("stderr.log")),
+                )
+            }
+            None => (None, None),
+        };
+        let max_bytes = capture.map_or(0, |c| c.max_bytes);
+        let (out, err) = (child.stdout.take(), child.stderr.take());
+        let (status, stdout, stderr) = tokio::join!(
+            wait_with_timeout(&mut child, self.timeout, grace),
+            save_output(out, stdout, max_bytes),
+            save_output(err, stderr, max_bytes),
+        );
+
+        run.status = match (status, self.timeout) {
+            (Ok(Some(status)), _) => match status.code() {
+                Some(code) => RunStatus::Exited(code),
+                None => RunStatus::Terminated,
+            },
+            (Ok(None), Some(timeout)) => RunStatus::TimedOut(timeout),
+            (Ok(None), None) => RunStatus::Terminated,
+            (Err(e), _) => {
+                RunStatus::Failed(format!("waiting for '{}' failed: {}", self.program, e))
+            }
+        };
+        run.finished = clock.now();
+        match (stdout, stderr) {
+            (Ok(stdout), Ok(stderr)) => {
+                run.stdout = stdout;
+                run.stderr = stderr;
+            }
+            (Err(e), _) | (_, Err(e)) => run.output_error = Some(format!("{:#}", e)),
+        }
+        run
+    }
+}
+
+impl std::fmt::Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", quote(&self.program))?;
+        for arg in &self.args {
+            write!(f, " {}", quote(arg))?;
+        }
+        Ok(())
+    }
+}
+
+/// How a started program ended.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RunStatus {
+    Exited(i32),
+    /// Ended by a signal, without an exit code.
+    Terminated,
+    /// Stopped after running longer than the action's timeout.
+    TimedOut(Duration),
+    /// The program could not be started or waited for.
+    Failed(String),
+}
+
+impl RunStatus {
+    pub fn is_success(&self) -> bool {
+        *self == RunStatus::Exited(0)
+    }
+}
+
+impl std::fmt::Display for RunStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunStatus::Exited(code) => write!(f, "exit code {}", code),
+            RunStatus::Terminated => write!(f, "terminated by a signal"),
+            RunStatus::TimedOut(limit) => write!(f, "timed out after {}", format_period(*limit)),
+            RunStatus::Failed(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// One execution of a task's [`Action`], kept on the task as its latest outcome.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub started: DateTime<Local>,
+    pub finished: DateTime<Local>,
+    /// The exit status, or the error that kept the program from running.
+    pub status: RunStatus,
+    /// The saved output, if it was captured and not empty.
+    #[serde(default)]
+    pub stdout: Option<PathBuf>,
+    #[serde(default)]
+    pub stderr: Option<PathBuf>,
+    /// Why the output could not be saved.
+    #[serde(default)]
+    pub output_error: Option<String>,
+}
+
+impl RunRecord {
+    pub fn duration(&self) -> chrono::Duration {
+        self.finished - self.started
+    }
+}
+
+/// Where [`Action::run`] saves a run's output, and how much of it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputCapture {
+    pub dir: PathBuf,
+    /// Start of the file names, normally the task name.
+    pub name: String,
+    /// Bytes kept per stream. Anything beyond is dropped and replaced by a note
+    /// saying how much was left out.
+    pub max_bytes: u64,
+}
+
+impl OutputCapture {
+    // Runs are told apart by start time and process id, e.g.
+    // `runs/Nightly_backup-20240508-090000-4242`
+    fn file_stem(&self, started: DateTime<Local>, pid: u32) -> PathBuf {
+        let name: String = self
+            .name
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.dir.join(format!(
+            "{}-{}-{}",
+            name,
+            started.format("%Y%m%d-%H%M%S"),
+            pid
+        ))
+    }
+}
+
+/// The folder run output is saved to, next to the tasks file and the `logs` folder.
+pub fn run_output_dir() -> Option<PathBuf> {
+    crate::project_file_path().map(|path| path.with_file_name("runs"))
+}
+
+/// Deletes the oldest files in `dir` until the rest take up at most `max_total_bytes`.
+/// Returns how many files were deleted.
+pub fn prune_run_output(dir: &Path, max_total_bytes: u64) -> Result<usize, anyhow::Error> {
+    let mut files: Vec<(SystemTime, u64, PathBuf)> = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("reading {}", dir.display()))? {
+        let entry = entry?;
+        let meta = entry.metadata()?;
+        if meta.is_file() {
+            let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            files.push((modified, meta.len(), entry.path()));
+        }
+    }
+    files.sort();
+
+    let mut total: u64 = files.iter().map(|(_, len, _)| len).sum();
+    let mut deleted = 0;
+    for (_, len, path) in files {
+        if total <= max_total_bytes {
+            break;
+        }
+        fs::remove_file(&path).with_context(|| format!("deleting {}", path.display()))?;
+        total -= len;
+        deleted += 1;
+    }
+    Ok(deleted)
+}
+
+// Waits for the program to exit. Past the timeout its process group gets SIGTERM,
+// then SIGKILL as soon as the program has exited or `grace` has run out, and `None`
+// is returned. The SIGKILL catches processes it started that ignored SIGTERM or
+// outlived it.
+async fn wait_with_timeout(
+    child: &mut Child,
+    timeout: Option<Duration>,
+    grace: Duration,
+) -> std::io::Result<Option<ExitStatus>> {
+    let Some(timeout) = timeout else {
+        return child.wait().await.map(Some);
+    };
+    if let Ok(status) = tokio::time::timeout(timeout, child.wait()).await {
+        return status.map(Some);
+    }
+    // No pid means the child has already been reaped: it exited just as the time
+    // ran out, so report how it ended
+    let Some(pid) = child.id() else {
+        return child.wait().await.map(Some);
+    };
+
+    #[cfg(unix)]
+    {
+        signal_group(pid, libc::SIGTERM);
+        // A program that stops on SIGTERM frees its run slot straight away
+        let _ = tokio::time::timeout(grace, child.wait()).await;
+        signal_group(pid, libc::SIGKILL);
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (pid, grace);
+        child.start_kill()?;
+    }
+    child.wait().await?;
+    Ok(None)
+}
+
+// Sends `signal` to every process in the group led by the child with pid `pgid`.
+#[cfg(unix)]
+fn signal_group(pgid: u32, signal: libc::c_int) {
+    // SAFETY: kill(2) takes plain integers and touches no memory of ours. The child
+    // was spawned with `process_group(0)`, so its pid is its process group id. Until
+    // the child is reaped that pid is not reused, and after that it stays reserved
+    // while any process it started is left in the group, since a pid is not handed
+    // out while a group with that id exists. Once the group is empty the call fails
+    // with ESRCH, which is fine to ignore; only a new process that took the pid and
+    // made itself a group leader within moments of the reap could be signalled.
+    unsafe {
+        libc::kill(-(pgid as libc::pid_t), signal);
+    }
+}
+
+// Copies a stream to `path`, keeping the first `max_bytes`. The stream is read to
+// the end even when nothing more is saved, so the program never blocks on a full
+// pipe. An empty stream leaves no file.
+async fn save_output(
+    stream: Option<impl AsyncRead + Unpin>,
+    path: Option<PathBuf>,
+    max_bytes: u64,
+) -> Result<Option<PathBuf>, anyhow::Error> {
+    let (Some(mut stream), Some(path)) = (stream, path) else {
+        return Ok(None);
+    };
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .with_context(|| format!("creating {}", dir.display()))?;
+    }
+    let mut file = tokio::fs::File::create(&path)
+        .await
+        .with_context(|| format!("creating {}", path.display()))?;
+
+    let mut buf = [0u8; 8192];
+    let mut total: u64 = 0;
+    let mut write_error = None;
+    loop {
+        let n = match stream.read(&mut buf).await {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) => {
+                write_error.get_or_insert(e);
+                break;
+            }
+        };
+        let keep = max_bytes.saturating_sub(total).min(n as u64) as usize;
+        if keep > 0 && write_error.is_none() {
+            if let Err(e) = file.write_all(&buf[..keep]).await {
+                write_error = Some(e);
+            }
+        }
+        total += n as u64;
+    }
+    if total > max_bytes && write_error.is_none() {
+        let note = format!(
+            "\n[output truncated: {} more bytes not saved]\n",
+            total - max_bytes
+        );
+        if let Err(e) = file.write_all(note.as_bytes()).await {
+            write_error = Some(e);
+        }
+    }
+    if let Some(e) = write_error {
+        return Err(e).with_context(|| format!("saving output to {}", path.display()));
+    }
+    file.flush()
+        .await
+        .with_context(|| format!("writing {}", path.display()))?;
+
+    if total == 0 {
+        drop(file);
+        let _ = tokio::fs::remove_file(&path).await;
+        return Ok(None);
+    }
+    Ok(Some(path))
+}
+
+/// Parses `KEY=value` pairs separated by spaces, quoted like command-line
+/// arguments, e.g. `RUST_LOG=debug TARGET="/mnt/My Backups"`.
+pub fn parse_env(input: &str) -> Result<BTreeMap<String, String>, anyhow::Error> {
+    split_words(input)?
+        .into_iter()
+        .map(|pair| match pair.split_once('=') {
+            Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+            _ => Err(anyhow!("'{}' is not of the form KEY=value", pair)),
+        })
+        .collect()
+}
+
+// Splits on whitespace outside quotes. Single quotes keep everything literally;
+// inside double quotes and bare words a backslash escapes the next character.
+fn split_words(line: &str) -> Result<Vec<String>, anyhow::Error> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '\'' => {
+                let current = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err(anyhow!("unterminated ' quote in: {}", line)),
+                    }
+                }
+            }
+            '"' => {
+                let current = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c) => current.push(c),
+                            None => return Err(anyhow!("unterminated \" quote in: {}", line)),
+                        },
+                        Some(c) => current.push(c),
+                        None => return Err(anyhow!("unterminated \" quote in: {}", line)),
+                    }
+                }
+            }
+            '\\' => {
+                let escaped = chars.next().unwrap_or('\\');
+                word.get_or_insert_with(String::new).push(escaped);
+            }
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+// Quotes a word for display when splitting it back would otherwise change it
+fn quote(word: &str) -> String {
+    let plain = !word.is_empty()
+        && !word
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '\'' | '"' | '\\'));
+    if plain {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ManualClock;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_command_line_round_trip() {
+        let action =
+            Action::from_command_line(r#"backup.sh --dest "/mnt/My Backups" 'it''s' a\ b"#)
+                .unwrap()
+                .unwrap();
+        assert_eq!(action.program, "backup.sh");
+        assert_eq!(action.args, vec!["--dest", "/mnt/My Backups", "its", "a b"]);
+        let again = Action::from_command_line(&action.to_string())
+            .unwrap()
+            .unwrap();
+        assert_eq!(again, action);
+
+        assert_eq!(Action::from_command_line("   ").unwrap(), None);
+        assert!(Action::from_command_line("echo \"oops").is_err());
+
+        let env = parse_env(r#"RUST_LOG=debug TARGET="/mnt/My Backups" EMPTY="#).unwrap();
+        assert_eq!(env["TARGET"], "/mnt/My Backups");
+        assert_eq!(env["EMPTY"], "");
+        assert!(parse_env("=value").is_err());
+        assert!(parse_env("NOVALUE").is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_reports_exit_status() {
+        let clock = ManualClock::new(Local.with_ymd_and_hms(2024, 5, 8, 9, 0, 0).unwrap());
+        let dir = tempfile::tempdir().unwrap();
+        let mut action = Action::from_command_line(r#"sh -c 'test "$PWD" = "$DIR" && exit 3'"#)
+            .unwrap()
+            .unwrap();
+        action.working_dir = Some(dir.path().canonicalize().unwrap());
+        action.env.insert(
+            "DIR".into(),
+            dir.path().canonicalize().unwrap().display().to_string(),
+        );
+
+        let run = action.run(&clock, None).await;
+        assert_eq!(run.status, RunStatus::Exited(3));
+        assert_eq!(run.started, clock.now());
+        assert_eq!(run.finished, clock.now());
+
+        let missing = Action::from_command_line("/nonexistent/program")
+            .unwrap()
+            .unwrap();
+        let run = missing.run(&clock, None).await;
+        assert!(matches!(run.status, RunStatus::Failed(_)));
+        assert!(!run.status.is_success());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_saves_capped_output() {
+        let clock = ManualClock::new(Local.with_ymd_and_hms(2024, 5, 8, 9, 0, 0).unwrap());
+        let dir = tempfile::tempdir().unwrap();
+        let capture = OutputCapture {
+            dir: dir.path().join("runs"),
+            name: "Nightly backup".into(),
+            max_bytes: 10,
+        };
+        let action = Action::from_command_line("sh -c 'printf 0123456789abcdef; echo oops >&2'")
+            .unwrap()
+            .unwrap();
+
+        let run = action.run(&clock, Some(&capture)).await;
+        assert!(run.status.is_success());
+        assert_eq!(run.output_error, None);
+        let (stdout, stderr) = (run.stdout.unwrap(), run.stderr.unwrap());
+        assert!(stdout
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("Nightly_backup-20240508-090000-"));
+        assert_eq!(
+            fs::read_to_string(&stdout).unwrap(),
+            "0123456789\n[output truncated: 6 more bytes not saved]\n"
+        );
+        assert_eq!(fs::read_to_string(&stderr).unwrap(), "oops\n");
+
+        // Nothing written leaves no file
+        let quiet = Action::from_command_line("true").unwrap().unwrap();
+        let run = quiet.run(&clock, Some(&capture)).await;
+        assert_eq!((run.stdout, run.stderr), (None, None));
+        assert_eq!(fs::read_dir(&capture.dir).unwrap().count(), 2);
+
+        // The oldest file goes first
+        fs::File::options()
+            .write(true)
+            .open(&stdout)
+            .unwrap()
+            .set_modified(SystemTime::UNIX_EPOCH)
+            .unwrap();
+        assert_eq!(prune_run_output(&capture.dir, 10).unwrap(), 1);
+        assert!(!stdout.exists() && stderr.exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_timeout_stops_process_group() {
+        let clock = ManualClock::new(Local.with_ymd_and_hms(2024, 5, 8, 9, 0, 0).unwrap());
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("survived");
+        // The shell ignores SIGTERM and leaves a child behind that would write the
+        // marker if it outlived the timeout
+        let script = format!(
+            "trap '' TERM; (sleep 1; touch {}) & sleep 30",
+            marker.display()
+        );
+        let mut action = Action {
+            program: "sh".into(),
+            args: vec!["-c".into(), script],
+            timeout: Some(Duration::from_millis(200)),
+            ..Action::default()
+        };
+
+        let started = std::time::Instant::now();
+        let run = action
+            .run_with_grace(&clock, None, Duration::from_millis(200))
+            .await;
+        assert_eq!(run.status, RunStatus::TimedOut(Duration::from_millis(200)));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert!(!marker.exists(), "The whole group was killed");
+
+        // The shell stops on SIGTERM, so the run ends without waiting out the grace
+        // period, and the child that ignores SIGTERM is killed with it
+        action.args[1] = format!(
+            "(trap '' TERM; sleep 1; touch {}) & sleep 30",
+            marker.display()
+        );
+        let started = std::time::Instant::now();
+        let run = action
+            .run_with_grace(&clock, None, Duration::from_secs(30))
+            .await;
+        assert!(matches!(run.status, RunStatus::TimedOut(_)));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert!(!marker.exists(), "The leftover child was killed");
+        assert_eq!(
+            RunStatus::TimedOut(Duration::from_secs(90)).to_string(),
+            "timed out after 90 seconds"
+        );
+
+        action.args = vec!["-c".into(), "exit 0".into()];
+        let run = action.run(&clock, None).await;
+        assert!(run.status.is_success());
+    }
+}
@@ -0,0 +1,230 @@
+use anyhow::{anyhow, bail};
+use chrono::{Datelike, Duration, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// A recurring period during which tasks must not start, such as 22:00-07:00 every
+/// night or all day on weekends.
+///
+/// `end` before `start` wraps past midnight, and `start == end` covers the whole
+/// day. `weekdays` lists the days the window starts on; empty means every day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlackoutWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    #[serde(default)]
+    pub weekdays: Vec<Weekday>,
+}
+
+impl BlackoutWindow {
+    /// A window covering the whole of each listed day.
+    pub fn all_day(weekdays: Vec<Weekday>) -> Self {
+        Self {
+            start: NaiveTime::MIN,
+            end: NaiveTime::MIN,
+            weekdays,
+        }
+    }
+
+    fn starts_on(&self, day: Weekday) -> bool {
+        self.weekdays.is_empty() || self.weekdays.contains(&day)
+    }
+
+    /// If `t` falls inside the window, the wall-clock time the window ends.
+    pub fn end_after(&self, t: NaiveDateTime) -> Option<NaiveDateTime> {
+        let date = t.date();
+        let time = t.time();
+        if self.start == self.end {
+            return self
+                .starts_on(date.weekday())
+                .then(|| (date + Duration::days(1)).and_time(self.end));
+        }
+        if self.start < self.end {
+            return (self.starts_on(date.weekday()) && time >= self.start && time < self.end)
+                .then(|| date.and_time(self.end));
+        }
+        // Wraps past midnight: either the evening part of today's window or the
+        // morning part of yesterday's
+        if time >= self.start && self.starts_on(date.weekday()) {
+            Some((date + Duration::days(1)).and_time(self.end))
+        } else if time < self.end && self.starts_on(date.pred_opt()?.weekday()) {
+            Some(date.and_time(self.end))
+        } else {
+            None
+        }
+    }
+}
+
+/// If `t` is inside any of `windows`, the first wall-clock time after it that is
+/// outside all of them (so back-to-back windows such as Saturday and Sunday chain),
+/// together with the window that covers `t`.
+pub fn blackout_end(
+    windows: &[BlackoutWindow],
+    t: NaiveDateTime,
+) -> Option<(NaiveDateTime, &BlackoutWindow)> {
+    let (mut end, covering) = windows
+        .iter()
+        .find_map(|w| w.end_after(t).map(|end| (end, w)))?;
+    // Bounded in case the windows cover the whole week
+    for _ in 0..32 {
+        match windows.iter().find_map(|w| w.end_after(end)) {
+            Some(next) => end = next,
+            None => break,
+        }
+    }
+    Some((end, covering))
+}
+
+impl std::fmt::Display for BlackoutWindow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let days = self
+            .weekdays
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>()
+            .join("/");
+        if self.start == self.end {
+            if days.is_empty() {
+                write!(f, "all day")
+            } else {
+                write!(f, "{}", days)
+            }
+        } else {
+            let times = format!(
+                "{}-{}",
+                self.start.format("%H:%M"),
+                self.end.format("%H:%M")
+            );
+            if days.is_empty() {
+                write!(f, "{}", times)
+            } else {
+                write!(f, "{} {}", days, times)
+            }
+        }
+    }
+}
+
+/// Parses "22:00-07:00", "Sat/Sun", "weekends" or "Mon/Fri 12:00-13:00".
+impl FromStr for BlackoutWindow {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (days, times) = match s.rsplit_once(char::is_whitespace) {
+            Some((days, times)) if times.contains(':') => (days.trim(), Some(times)),
+            None if s.contains(':') => ("", Some(s)),
+            _ => (s, None),
+        };
+
+        let weekdays = match days.to_ascii_lowercase().as_str() {
+            "" | "daily" | "all day" => vec![],
+            "weekends" => vec![Weekday::Sat, Weekday::Sun],
+            "weekdays" => vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ],
+            _ => days
+                .split('/')
+                .map(|d| {
+                    d.trim()
+                        .parse::<Weekday>()
+                        .map_err(|_| anyhow!("unrecognized weekday '{}'", d.trim()))
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+        };
+
+        let Some(times) = times else {
+            return Ok(BlackoutWindow::all_day(weekdays));
+        };
+        let (start, end) = times
+            .split_once('-')
+            .ok_or_else(|| anyhow!("expected a time range like 22:00-07:00, got '{}'", times))?;
+        let parse = |t: &str| {
+            NaiveTime::parse_from_str(t.trim(), "%H:%M")
+                .map_err(|_| anyhow!("'{}' is not a time in HH:MM format", t.trim()))
+        };
+        let (start, end) = (parse(start)?, parse(end)?);
+        if start == end && start != NaiveTime::MIN {
+            bail!("blackout window {} is empty", times);
+        }
+        Ok(BlackoutWindow {
+            start,
+            end,
+            weekdays,
+        })
+    }
+}
+
+/// Parses a comma-separated list of windows, e.g. "22:00-07:00, weekends".
+pub fn parse_blackouts(s: &str) -> Result<Vec<BlackoutWindow>, anyhow::Error> {
+    s.split(',')
+        .filter(|part| !part.trim().is_empty())
+        .map(|part| part.parse())
+        .collect()
+}
+
+/// Formats windows in the form accepted by [`parse_blackouts`].
+pub fn format_blackouts(windows: &[BlackoutWindow]) -> String {
+    windows
+        .iter()
+        .map(|w| w.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dt(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_overnight_window() {
+        let quiet: BlackoutWindow = "22:00-07:00".parse().unwrap();
+        assert_eq!(quiet.end_after(dt("2024-05-06 21:59")), None);
+        assert_eq!(
+            quiet.end_after(dt("2024-05-06 23:30")),
+            Some(dt("2024-05-07 07:00"))
+        );
+        assert_eq!(
+            quiet.end_after(dt("2024-05-07 03:00")),
+            Some(dt("2024-05-07 07:00"))
+        );
+        assert_eq!(quiet.end_after(dt("2024-05-07 07:00")), None);
+    }
+
+    #[test]
+    fn test_overnight_window_uses_start_day() {
+        // Friday night only: Saturday 03:00 is covered, Friday 03:00 is not
+        let window: BlackoutWindow = "Fri 22:00-07:00".parse().unwrap();
+        assert!(window.end_after(dt("2024-05-11 03:00")).is_some());
+        assert!(window.end_after(dt("2024-05-10 03:00")).is_none());
+    }
+
+    #[test]
+    fn test_weekend_windows_chain() {
+        let windows = parse_blackouts("weekends, 22:00-07:00").unwrap();
+        // Friday 23:00 -> quiet hours, then Saturday and Sunday, then Monday 00:00
+        let (end, covering) = blackout_end(&windows, dt("2024-05-10 23:00")).unwrap();
+        assert_eq!(end, dt("2024-05-13 07:00"));
+        assert_eq!(covering.to_string(), "22:00-07:00");
+        assert_eq!(blackout_end(&windows, dt("2024-05-13 12:00")), None);
+    }
+
+    #[test]
+    fn test_blackout_parse_round_trip() {
+        let windows = parse_blackouts("Sat/Sun, Mon/Fri 12:00-13:00, 22:00-07:00").unwrap();
+        assert_eq!(
+            format_blackouts(&windows),
+            "Sat/Sun, Mon/Fri 12:00-13:00, 22:00-07:00"
+        );
+        assert!(parse_blackouts("Funday").is_err());
+        assert!(parse_blackouts("10:00").is_err());
+        assert!(parse_blackouts("").unwrap().is_empty());
+    }
+}
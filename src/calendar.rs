@@ -0,0 +1,183 @@
+use anyhow::{anyhow, Context};
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// How far `next_business_day` looks before giving up on a calendar that excludes
+// every day.
+const SEARCH_LIMIT_DAYS: usize = 366;
+
+/// A named set of business days that tasks can reference: optionally weekdays only,
+/// minus a list of holidays.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BusinessCalendar {
+    pub name: String,
+    /// Excludes Saturdays and Sundays.
+    #[serde(default)]
+    pub weekdays_only: bool,
+    #[serde(default)]
+    pub holidays: BTreeSet<NaiveDate>,
+    /// The file the holidays were imported from, kept for display.
+    #[serde(default)]
+    pub source: Option<PathBuf>,
+}
+
+impl BusinessCalendar {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            weekdays_only: true,
+            holidays: BTreeSet::new(),
+            source: None,
+        }
+    }
+
+    pub fn is_business_day(&self, date: NaiveDate) -> bool {
+        let weekend = matches!(date.weekday(), Weekday::Sat | Weekday::Sun);
+        let excluded = (self.weekdays_only && weekend) || self.holidays.contains(&date);
+        !excluded
+    }
+
+    /// The first business day strictly after `date`.
+    pub fn next_business_day(&self, date: NaiveDate) -> Option<NaiveDate> {
+        date.iter_days()
+            .skip(1)
+            .take(SEARCH_LIMIT_DAYS)
+            .find(|d| self.is_business_day(*d))
+    }
+}
+
+/// Reads holiday dates from an iCalendar (`.ics`) file, or otherwise from a plain
+/// list with one `YYYY-MM-DD` date per line.
+pub fn load_holidays(path: &Path) -> Result<Vec<NaiveDate>, anyhow::Error> {
+    let data = fs::read_to_string(path)
+        .with_context(|| format!("reading holiday file {}", path.display()))?;
+    let is_ics = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("ics"));
+    if is_ics {
+        parse_ics_dates(&data)
+    } else {
+        parse_holiday_list(&data)
+    }
+    .with_context(|| format!("parsing holiday file {}", path.display()))
+}
+
+/// Parses one `YYYY-MM-DD` date per line. Anything after the date (such as the
+/// holiday's name), blank lines and `#` comments are ignored.
+pub fn parse_holiday_list(data: &str) -> Result<Vec<NaiveDate>, anyhow::Error> {
+    data.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .enumerate()
+        .map(|(i, line)| {
+            let date = line.split_whitespace().next().unwrap_or(line);
+            NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map_err(|_| anyhow!("entry {}: '{}' is not a YYYY-MM-DD date", i + 1, date))
+        })
+        .collect()
+}
+
+/// Collects the days covered by each `VEVENT` in an iCalendar file. All-day events
+/// spanning several days (an exclusive `DTEND`) contribute every day they cover.
+pub fn parse_ics_dates(data: &str) -> Result<Vec<NaiveDate>, anyhow::Error> {
+    // Continuation lines start with a space or tab and belong to the previous line
+    let mut lines: Vec<String> = Vec::new();
+    for line in data.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.trim_end().to_string()),
+        }
+    }
+
+    let mut dates = Vec::new();
+    let (mut start, mut end) = (None, None);
+    for line in &lines {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        // Drop parameters such as ";VALUE=DATE" or ";TZID=Europe/Berlin"
+        let property = key.split(';').next().unwrap_or(key).to_ascii_uppercase();
+        match property.as_str() {
+            "BEGIN" if value.eq_ignore_ascii_case("VEVENT") => (start, end) = (None, None),
+            "DTSTART" => start = Some(parse_ics_date(value)?),
+            "DTEND" if key.to_ascii_uppercase().contains("VALUE=DATE") => {
+                end = Some(parse_ics_date(value)?)
+            }
+            "END" if value.eq_ignore_ascii_case("VEVENT") => {
+                let first = start.ok_or_else(|| anyhow!("event without DTSTART"))?;
+                dates.extend(
+                    first
+                        .iter_days()
+                        .take_while(|d| end.map_or(*d == first, |end| *d < end))
+                        .take(SEARCH_LIMIT_DAYS),
+                );
+            }
+            _ => {}
+        }
+    }
+    Ok(dates)
+}
+
+// The date part of "20241225" or "20241225T090000Z"
+fn parse_ics_date(value: &str) -> Result<NaiveDate, anyhow::Error> {
+    let digits = value.trim().get(..8).unwrap_or(value);
+    NaiveDate::parse_from_str(digits, "%Y%m%d")
+        .map_err(|_| anyhow!("'{}' is not an iCalendar date", value.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_next_business_day_skips_weekend_and_holidays() {
+        let mut calendar = BusinessCalendar::new("Bank");
+        calendar.holidays.insert(date(2024, 12, 25));
+        calendar.holidays.insert(date(2024, 12, 26));
+
+        assert!(!calendar.is_business_day(date(2024, 12, 25)));
+        assert!(!calendar.is_business_day(date(2024, 12, 28)));
+        // Tue 24th -> Fri 27th, Fri 27th -> Mon 30th
+        assert_eq!(
+            calendar.next_business_day(date(2024, 12, 24)),
+            Some(date(2024, 12, 27))
+        );
+        assert_eq!(
+            calendar.next_business_day(date(2024, 12, 27)),
+            Some(date(2024, 12, 30))
+        );
+    }
+
+    #[test]
+    fn test_parse_holiday_list() {
+        let dates =
+            parse_holiday_list("# UK bank holidays\n2024-12-25 Christmas\n\n2024-12-26\n").unwrap();
+        assert_eq!(dates, vec![date(2024, 12, 25), date(2024, 12, 26)]);
+        assert!(parse_holiday_list("25/12/2024").is_err());
+    }
+
+    #[test]
+    fn test_parse_ics_dates() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+                   BEGIN:VEVENT\r\n\
+                   SUMMARY:Christmas\r\n\
+                   DTSTART;VALUE=DATE:20241225\r\n\
+                   DTEND;VALUE=DATE:20241227\r\n\
+                   END:VEVENT\r\n\
+                   BEGIN:VEVENT\r\n\
+                   DTSTART:20250101T000000Z\r\n\
+                   END:VEVENT\r\n\
+                   END:VCALENDAR\r\n";
+        assert_eq!(
+            parse_ics_dates(ics).unwrap(),
+            vec![date(2024, 12, 25), date(2024, 12, 26), date(2025, 1, 1)]
+        );
+    }
+}
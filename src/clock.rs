@@ -0,0 +1,66 @@
+use chrono::{DateTime, Duration, Local};
+use std::sync::{Arc, Mutex};
+
+/// The source of "now" for scheduling decisions. The app reads the time through a
+/// clock instead of calling `Local::now()` directly, so tests and simulations can
+/// control it.
+pub trait Clock: std::fmt::Debug + Send + Sync {
+    fn now(&self) -> DateTime<Local>;
+}
+
+/// The computer's real clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+}
+
+/// A clock that only moves when told to. Clones share the same time, so a test can
+/// keep one handle and pass another to the code under test.
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    now: Arc<Mutex<DateTime<Local>>>,
+}
+
+impl ManualClock {
+    pub fn new(now: DateTime<Local>) -> Self {
+        Self {
+            now: Arc::new(Mutex::new(now)),
+        }
+    }
+
+    pub fn set(&self, now: DateTime<Local>) {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner()) = now;
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner()) += by;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Local> {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_manual_clock_is_shared_between_clones() {
+        let start = Local.with_ymd_and_hms(2024, 5, 8, 9, 0, 0).unwrap();
+        let clock = ManualClock::new(start);
+        let handle: Arc<dyn Clock> = Arc::new(clock.clone());
+
+        clock.advance(Duration::minutes(90));
+        assert_eq!(handle.now(), start + Duration::minutes(90));
+        clock.set(start);
+        assert_eq!(handle.now(), start);
+    }
+}
@@ -0,0 +1,287 @@
+use anyhow::{anyhow, bail, Context};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use std::str::FromStr;
+
+const MONTH_NAMES: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const DAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+// How far ahead `next_after` searches before concluding an expression never matches
+// (e.g. "0 0 30 2 *"). Four years covers every leap-year cycle.
+const SEARCH_LIMIT_DAYS: i64 = 366 * 4 + 1;
+
+/// A parsed cron expression.
+///
+/// Accepts the standard 5-field form (`minute hour day-of-month month day-of-week`)
+/// and the 6-field form with a leading seconds field. Each field supports `*`, `?`,
+/// single values, ranges (`1-5`), steps (`*/15`, `10-40/5`), lists (`1,15,30`) and,
+/// for months and weekdays, three-letter names (`JAN`, `MON-FRI`). Day-of-week
+/// accepts both 0 and 7 for Sunday.
+///
+/// As in Vixie cron, when both day-of-month and day-of-week are restricted a day
+/// matches if either field matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    seconds: u64,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    dom_restricted: bool,
+    dow_restricted: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expr: &str) -> Result<Self, anyhow::Error> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let (sec, rest) = match fields.len() {
+            5 => ("0", &fields[..]),
+            6 => (fields[0], &fields[1..]),
+            n => bail!("expected 5 or 6 fields in cron expression, found {}", n),
+        };
+
+        let seconds = parse_field(sec, 0, 59, &[]).context("invalid seconds field")?;
+        let minutes = parse_field(rest[0], 0, 59, &[]).context("invalid minute field")?;
+        let hours = parse_field(rest[1], 0, 23, &[]).context("invalid hour field")?;
+        let days_of_month =
+            parse_field(rest[2], 1, 31, &[]).context("invalid day-of-month field")?;
+        let months = parse_field(rest[3], 1, 12, &MONTH_NAMES).context("invalid month field")?;
+        let mut days_of_week =
+            parse_field(rest[4], 0, 7, &DAY_NAMES).context("invalid day-of-week field")?;
+
+        // Fold 7 (Sunday) onto 0 so lookups only need to check 0..=6
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week & !(1 << 7)) | 1;
+        }
+
+        Ok(Self {
+            seconds,
+            minutes,
+            hours,
+            days_of_month,
+            months,
+            days_of_week,
+            dom_restricted: !is_wildcard(rest[2]),
+            dow_restricted: !is_wildcard(rest[4]),
+        })
+    }
+
+    /// Returns true if `dt` (truncated to whole seconds) matches the expression.
+    pub fn matches(&self, dt: NaiveDateTime) -> bool {
+        self.matches_date(dt.date())
+            && bit(self.hours, dt.hour())
+            && bit(self.minutes, dt.minute())
+            && bit(self.seconds, dt.second())
+    }
+
+    /// The first matching wall-clock time strictly after `after`, or `None` if the
+    /// expression has no match within the next four years.
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let limit = after + Duration::days(SEARCH_LIMIT_DAYS);
+        let mut t = after.with_nanosecond(0)? + Duration::seconds(1);
+
+        while t <= limit {
+            if !bit(self.months, t.month()) {
+                t = first_of_next_month(t.date())?.and_time(NaiveTime::MIN);
+                continue;
+            }
+            if !self.matches_date(t.date()) {
+                t = t.date().succ_opt()?.and_time(NaiveTime::MIN);
+                continue;
+            }
+            if !bit(self.hours, t.hour()) {
+                t = t.with_minute(0)?.with_second(0)? + Duration::hours(1);
+                continue;
+            }
+            if !bit(self.minutes, t.minute()) {
+                t = t.with_second(0)? + Duration::minutes(1);
+                continue;
+            }
+            if !bit(self.seconds, t.second()) {
+                t += Duration::seconds(1);
+                continue;
+            }
+            return Some(t);
+        }
+
+        None
+    }
+
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        if !bit(self.months, date.month()) {
+            return false;
+        }
+        let dom = bit(self.days_of_month, date.day());
+        let dow = bit(self.days_of_week, date.weekday().num_days_from_sunday());
+        match (self.dom_restricted, self.dow_restricted) {
+            (true, true) => dom || dow,
+            (true, false) => dom,
+            (false, true) => dow,
+            (false, false) => true,
+        }
+    }
+}
+
+impl FromStr for CronSchedule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+fn bit(mask: u64, value: u32) -> bool {
+    mask & (1 << value) != 0
+}
+
+fn is_wildcard(field: &str) -> bool {
+    field == "*" || field == "?"
+}
+
+fn first_of_next_month(date: NaiveDate) -> Option<NaiveDate> {
+    if date.month() == 12 {
+        NaiveDate::from_ymd_opt(date.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(date.year(), date.month() + 1, 1)
+    }
+}
+
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64, anyhow::Error> {
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .map_err(|_| anyhow!("invalid step '{}'", step))?;
+                if step == 0 {
+                    bail!("step must be greater than zero");
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = if is_wildcard(range) {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (parse_value(a, names)?, parse_value(b, names)?)
+        } else {
+            let v = parse_value(range, names)?;
+            // "5/15" means "from 5 to the end of the range, every 15"
+            (v, if step > 1 { max } else { v })
+        };
+
+        if start < min || end > max {
+            bail!("value out of range {}-{} in '{}'", min, max, part);
+        }
+        if start > end {
+            bail!("range start is after range end in '{}'", part);
+        }
+
+        for v in (start..=end).step_by(step as usize) {
+            mask |= 1 << v;
+        }
+    }
+    Ok(mask)
+}
+
+fn parse_value(s: &str, names: &[&str]) -> Result<u32, anyhow::Error> {
+    if let Ok(v) = s.parse::<u32>() {
+        return Ok(v);
+    }
+    let upper = s.to_ascii_uppercase();
+    names
+        .iter()
+        .position(|n| *n == upper)
+        .map(|idx| {
+            // Month names are 1-based, weekday names start at Sunday = 0
+            if names.len() == 12 {
+                idx as u32 + 1
+            } else {
+                idx as u32
+            }
+        })
+        .ok_or_else(|| anyhow!("unrecognized value '{}'", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dt(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_every_fifteen_minutes() {
+        let cron = CronSchedule::parse("*/15 * * * *").unwrap();
+        assert_eq!(
+            cron.next_after(dt("2024-03-10 10:07:30")),
+            Some(dt("2024-03-10 10:15:00"))
+        );
+        assert_eq!(
+            cron.next_after(dt("2024-03-10 10:45:00")),
+            Some(dt("2024-03-10 11:00:00"))
+        );
+    }
+
+    #[test]
+    fn test_named_weekdays_and_ranges() {
+        // 2024-03-08 is a Friday
+        let cron = CronSchedule::parse("30 9 * * mon-fri").unwrap();
+        assert_eq!(
+            cron.next_after(dt("2024-03-08 10:00:00")),
+            Some(dt("2024-03-11 09:30:00"))
+        );
+    }
+
+    #[test]
+    fn test_six_field_expression_with_seconds() {
+        let cron = CronSchedule::parse("10,40 0 12 * * *").unwrap();
+        assert_eq!(
+            cron.next_after(dt("2024-01-01 12:00:10")),
+            Some(dt("2024-01-01 12:00:40"))
+        );
+    }
+
+    #[test]
+    fn test_day_of_month_or_day_of_week() {
+        // The 1st of the month or any Sunday; 2024-03-02 is a Saturday
+        let cron = CronSchedule::parse("0 0 1 * SUN").unwrap();
+        assert_eq!(
+            cron.next_after(dt("2024-03-02 00:00:00")),
+            Some(dt("2024-03-03 00:00:00"))
+        );
+        assert_eq!(
+            cron.next_after(dt("2024-03-31 00:00:00")),
+            Some(dt("2024-04-01 00:00:00"))
+        );
+    }
+
+    #[test]
+    fn test_sunday_as_seven_and_month_names() {
+        let cron = CronSchedule::parse("0 8 * FEB 7").unwrap();
+        assert_eq!(
+            cron.next_after(dt("2024-01-15 00:00:00")),
+            Some(dt("2024-02-04 08:00:00"))
+        );
+    }
+
+    #[test]
+    fn test_impossible_date_never_matches() {
+        let cron = CronSchedule::parse("0 0 30 2 *").unwrap();
+        assert_eq!(cron.next_after(dt("2024-01-01 00:00:00")), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(CronSchedule::parse("* * * *").is_err());
+        assert!(CronSchedule::parse("60 * * * *").is_err());
+        assert!(CronSchedule::parse("* * * * FOO").is_err());
+        assert!(CronSchedule::parse("*/0 * * * *").is_err());
+        assert!(CronSchedule::parse("5-1 * * * *").is_err());
+    }
+}
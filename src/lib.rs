@@ -1,16 +1,390 @@
 use anyhow::{anyhow, Context};
-use chrono::{DateTime, Local};
+use chrono::{
+    DateTime, Datelike, Local, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone,
+    Utc, Weekday,
+};
+use chrono_tz::Tz;
 use directories::ProjectDirs;
+use rand::seq::SliceRandom;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub mod action;
+pub mod blackout;
+pub mod calendar;
+pub mod clock;
+pub mod cron;
+pub mod phrase;
+pub mod queue;
+pub mod scheduler;
+pub mod settings;
+pub mod simulate;
+
+pub use action::{
+    parse_env, prune_run_output, run_output_dir, Action, OutputCapture, RunRecord, RunStatus,
+};
+pub use blackout::{blackout_end, format_blackouts, parse_blackouts, BlackoutWindow};
+pub use calendar::{load_holidays, BusinessCalendar};
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use cron::CronSchedule;
+pub use phrase::parse_phrase;
+pub use queue::{Priority, QueuedRun, RunQueue};
+pub use scheduler::{DueEvent, Scheduler};
+pub use settings::{load_settings, save_settings, settings_file_path, Settings};
+pub use simulate::{simulate, SimulatedRun, Simulation};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Interval {
     Hourly,
     Daily,
     Weekly,
+    /// A 5- or 6-field cron expression, evaluated against local wall-clock time.
+    Cron(String),
+    /// A fixed period measured from the last run, e.g. every 15 minutes.
+    Every(Duration),
+    /// A fixed local time of day, on the given weekdays or every day when empty.
+    At {
+        time: NaiveTime,
+        weekdays: Vec<Weekday>,
+    },
+    /// Once a month at a local time of day.
+    Monthly {
+        day: MonthDay,
+        time: NaiveTime,
+    },
+    /// Once a year on a calendar date at a local time of day. Feb 29 runs on Feb 28
+    /// in non-leap years.
+    Yearly {
+        month: u32,
+        day: u32,
+        time: NaiveTime,
+    },
+    /// Runs a single time once the given moment has passed, then disables itself.
+    Once(DateTime<Local>),
+}
+
+/// The day a `Monthly` interval runs on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MonthDay {
+    /// A day of the month (1-31). In months without that day the task runs on the
+    /// month's last day instead, so day 31 runs on Apr 30 and Feb 28/29.
+    Day(u32),
+    /// The last occurrence of a weekday in the month, e.g. the last Friday.
+    Last(Weekday),
+    /// The nth (1-4) occurrence of a weekday in the month, e.g. the first Monday.
+    Nth(u32, Weekday),
+}
+
+impl MonthDay {
+    /// The date this rule selects in the given month.
+    pub fn date_in(self, year: i32, month: u32) -> Option<NaiveDate> {
+        let last_day = days_in_month(year, month)?;
+        match self {
+            MonthDay::Day(day) => NaiveDate::from_ymd_opt(year, month, day.min(last_day)),
+            MonthDay::Last(weekday) => {
+                let last = NaiveDate::from_ymd_opt(year, month, last_day)?;
+                let back = (7 + last.weekday().num_days_from_monday()
+                    - weekday.num_days_from_monday())
+                    % 7;
+                last.checked_sub_days(chrono::Days::new(back as u64))
+            }
+            MonthDay::Nth(n, weekday) => {
+                let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+                let ahead = (7 + weekday.num_days_from_monday()
+                    - first.weekday().num_days_from_monday())
+                    % 7;
+                let day = 1 + ahead + 7 * n.checked_sub(1)?;
+                (day <= last_day)
+                    .then(|| NaiveDate::from_ymd_opt(year, month, day))
+                    .flatten()
+            }
+        }
+    }
+}
+
+const ORDINALS: [&str; 4] = ["first", "second", "third", "fourth"];
+
+// Resolves a wall-clock time the same way scheduled slots are: the first occurrence
+// of a repeated time, and times in a spring-forward gap shifted past it.
+fn resolve_local<Z: TimeZone>(tz: &Z, naive: NaiveDateTime) -> Option<DateTime<Z>> {
+    match tz.from_local_datetime(&naive) {
+        LocalResult::Single(dt) => Some(dt),
+        LocalResult::Ambiguous(first, _) => Some(first),
+        LocalResult::None => shift_past_gap(tz, naive),
+    }
+}
+
+fn shift_past_gap<Z: TimeZone>(tz: &Z, naive: NaiveDateTime) -> Option<DateTime<Z>> {
+    let before = naive - chrono::Duration::days(1);
+    let offset = tz.offset_from_local_datetime(&before).earliest()?.fix();
+    let utc = naive - chrono::Duration::seconds(offset.local_minus_utc() as i64);
+    Some(Utc.from_utc_datetime(&utc).with_timezone(tz))
+}
+
+const MONTH_ABBREVIATIONS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+fn days_in_month(year: i32, month: u32) -> Option<u32> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+    let next = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)?
+    };
+    Some(next.signed_duration_since(first).num_days() as u32)
+}
+
+impl Interval {
+    /// Builds a `Cron` interval, rejecting expressions that do not parse.
+    pub fn cron(expr: impl Into<String>) -> Result<Self, anyhow::Error> {
+        let expr = expr.into().trim().to_string();
+        CronSchedule::parse(&expr)
+            .with_context(|| format!("invalid cron expression '{}'", expr))?;
+        Ok(Interval::Cron(expr))
+    }
+
+    /// Checks that the interval can be evaluated: cron expressions must parse, fixed
+    /// periods must be non-zero and calendar days must exist in at least some years.
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        match self {
+            Interval::Monthly {
+                day: MonthDay::Day(day),
+                ..
+            } if !(1..=31).contains(day) => {
+                return Err(anyhow!(
+                    "day of month must be between 1 and 31, got {}",
+                    day
+                ));
+            }
+            Interval::Monthly {
+                day: MonthDay::Nth(n, _),
+                ..
+            } if !(1..=4).contains(n) => {
+                return Err(anyhow!(
+                    "weekday occurrence must be between 1 and 4, got {}",
+                    n
+                ));
+            }
+            Interval::Yearly { month, day, .. } => {
+                // 2024 is a leap year, so Feb 29 is accepted
+                let last_day = days_in_month(2024, *month)
+                    .ok_or_else(|| anyhow!("month must be between 1 and 12, got {}", month))?;
+                if !(1..=last_day).contains(day) {
+                    return Err(anyhow!(
+                        "{} has no day {}",
+                        MONTH_ABBREVIATIONS[*month as usize - 1],
+                        day
+                    ));
+                }
+            }
+            Interval::Cron(expr) => {
+                CronSchedule::parse(expr)
+                    .with_context(|| format!("invalid cron expression '{}'", expr))?;
+            }
+            Interval::Every(period) if period.is_zero() => {
+                return Err(anyhow!("repeat period must be greater than zero"));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// For calendar-anchored intervals, the first scheduled instant strictly after
+    /// `after`, evaluating wall-clock rules in `after`'s time zone.
+    ///
+    /// Daylight-saving transitions are resolved deterministically:
+    /// - a wall-clock time skipped by spring-forward runs shifted by the length of the
+    ///   gap (02:30 in a 02:00-03:00 gap runs at 03:30);
+    /// - a wall-clock time repeated by fall-back runs once, at its first occurrence.
+    pub fn next_after_in<Z: TimeZone>(&self, after: &DateTime<Z>) -> Option<DateTime<Z>> {
+        let tz = after.timezone();
+        let mut cursor = after.naive_local();
+        // Each pass moves the wall-clock cursor forward, so this only repeats for the
+        // handful of slots inside a fall-back overlap that resolve before `after`.
+        for _ in 0..1000 {
+            let naive = self.next_wall_clock_after(cursor)?;
+            let candidate = resolve_local(&tz, naive)?;
+            if candidate > *after {
+                return Some(candidate);
+            }
+            cursor = naive;
+        }
+        None
+    }
+
+    /// For calendar-anchored intervals, the first scheduled wall-clock time strictly
+    /// after `after`. Returns `None` for intervals measured from the last run, and
+    /// for schedules that never match.
+    pub fn next_wall_clock_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        match self {
+            Interval::Cron(expr) => CronSchedule::parse(expr).ok()?.next_after(after),
+            Interval::At { time, weekdays } => {
+                // Today's slot may still be ahead; otherwise one of the next 7 days matches
+                let mut date = after.date();
+                for _ in 0..8 {
+                    let candidate = date.and_time(*time);
+                    if candidate > after
+                        && (weekdays.is_empty() || weekdays.contains(&date.weekday()))
+                    {
+                        return Some(candidate);
+                    }
+                    date = date.succ_opt()?;
+                }
+                None
+            }
+            Interval::Monthly { day, time } => {
+                let (mut year, mut month) = (after.year(), after.month());
+                for _ in 0..13 {
+                    let candidate = day.date_in(year, month)?.and_time(*time);
+                    if candidate > after {
+                        return Some(candidate);
+                    }
+                    (year, month) = if month == 12 {
+                        (year + 1, 1)
+                    } else {
+                        (year, month + 1)
+                    };
+                }
+                None
+            }
+            Interval::Yearly { month, day, time } => (after.year()..=after.year() + 1)
+                .filter_map(|year| MonthDay::Day(*day).date_in(year, *month))
+                .map(|date| date.and_time(*time))
+                .find(|candidate| *candidate > after),
+            Interval::Hourly
+            | Interval::Daily
+            | Interval::Weekly
+            | Interval::Every(_)
+            | Interval::Once(_) => None,
+        }
+    }
+}
+
+const PERIOD_UNITS: [(u64, &str); 4] = [
+    (7 * 24 * 60 * 60, "week"),
+    (24 * 60 * 60, "day"),
+    (60 * 60, "hour"),
+    (60, "minute"),
+];
+
+// Formats a period using the largest unit that divides it evenly ("3 days", "90 minutes").
+pub(crate) fn format_period(period: Duration) -> String {
+    let secs = period.as_secs();
+    let (count, unit) = PERIOD_UNITS
+        .iter()
+        .find(|(unit_secs, _)| secs > 0 && secs.is_multiple_of(*unit_secs))
+        .map(|(unit_secs, name)| (secs / unit_secs, *name))
+        .unwrap_or((secs, "second"));
+    if count == 1 {
+        format!("1 {}", unit)
+    } else {
+        format!("{} {}s", count, unit)
+    }
+}
+
+fn format_at(time: NaiveTime, weekdays: &[Weekday]) -> String {
+    let days = if weekdays.is_empty() {
+        "Daily".to_string()
+    } else {
+        weekdays
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>()
+            .join("/")
+    };
+    format!("{} at {}", days, time.format("%H:%M"))
+}
+
+// Splits "<rule> at HH:MM" into the rule and the time of day.
+fn split_time_of_day(s: &str) -> Option<(&str, NaiveTime)> {
+    let (rule, time) = s.trim().rsplit_once(" at ")?;
+    let time = NaiveTime::parse_from_str(time.trim(), "%H:%M").ok()?;
+    Some((rule.trim(), time))
+}
+
+// Parses "Daily at 09:30" or "Mon/Wed/Fri at 18:00", matching the Display output.
+fn parse_at(s: &str) -> Option<Interval> {
+    let (days, time) = split_time_of_day(s)?;
+    let weekdays = if days.eq_ignore_ascii_case("daily") || days.eq_ignore_ascii_case("every day") {
+        vec![]
+    } else {
+        days.split('/')
+            .map(|d| d.trim().parse::<Weekday>().ok())
+            .collect::<Option<Vec<_>>>()?
+    };
+    Some(Interval::At { time, weekdays })
+}
+
+// Parses "Monthly on day 31 at 09:00", "Monthly on the last Fri at 17:00" or
+// "Monthly on the first Mon at 09:00".
+fn parse_monthly(s: &str) -> Option<Interval> {
+    let (rule, time) = split_time_of_day(s)?;
+    let rule = rule.to_ascii_lowercase();
+    let rule = rule.strip_prefix("monthly on")?.trim();
+    let day = if let Some(day) = rule.strip_prefix("day") {
+        MonthDay::Day(day.trim().parse().ok()?)
+    } else {
+        let rule = rule.strip_prefix("the").unwrap_or(rule).trim();
+        let (which, weekday) = rule.split_once(char::is_whitespace)?;
+        let weekday = weekday.trim().parse().ok()?;
+        match ORDINALS.iter().position(|o| *o == which) {
+            Some(i) => MonthDay::Nth(i as u32 + 1, weekday),
+            None if which == "last" => MonthDay::Last(weekday),
+            None => return None,
+        }
+    };
+    Some(Interval::Monthly { day, time })
+}
+
+// Parses "Yearly on Feb 29 at 09:00".
+fn parse_yearly(s: &str) -> Option<Interval> {
+    let (rule, time) = split_time_of_day(s)?;
+    let rule = rule.to_ascii_lowercase();
+    let (month, day) = rule.strip_prefix("yearly on")?.trim().split_once(' ')?;
+    let month = month.parse::<chrono::Month>().ok()?.number_from_month();
+    let day = day.trim().parse().ok()?;
+    Some(Interval::Yearly { month, day, time })
+}
+
+// Parses "Once at 2024-05-01 09:00" in local time.
+fn parse_once(s: &str) -> Option<Interval> {
+    let at = s.trim().get("once at".len()..)?.trim();
+    let naive = NaiveDateTime::parse_from_str(at, "%Y-%m-%d %H:%M").ok()?;
+    naive
+        .and_local_timezone(Local)
+        .earliest()
+        .map(Interval::Once)
+}
+
+// Parses "15 minutes", "every 3 days", "1 hour" and similar into a period.
+fn parse_period(s: &str) -> Option<Duration> {
+    let lower = s.trim().to_ascii_lowercase();
+    let rest = lower.strip_prefix("every").unwrap_or(&lower).trim();
+    let (count, unit) = rest.split_once(char::is_whitespace)?;
+    let count: u64 = count.trim().parse().ok()?;
+    Some(Duration::from_secs(count.checked_mul(unit_seconds(unit)?)?))
+}
+
+// Seconds in a unit name such as "week", "hours" or "min". Shared with the
+// plain-language schedule parser so both accept the same names.
+pub(crate) fn unit_seconds(unit: &str) -> Option<u64> {
+    let unit = unit.trim();
+    let unit = unit.strip_suffix('s').unwrap_or(unit);
+    match unit {
+        "week" | "wk" => Some(7 * 24 * 60 * 60),
+        "day" => Some(24 * 60 * 60),
+        "hour" | "hr" | "h" => Some(60 * 60),
+        "minute" | "min" | "m" => Some(60),
+        "second" | "sec" => Some(1),
+        _ => None,
+    }
 }
 
 impl std::fmt::Display for Interval {
@@ -19,16 +393,221 @@ impl std::fmt::Display for Interval {
             Interval::Hourly => write!(f, "Hourly"),
             Interval::Daily => write!(f, "Daily"),
             Interval::Weekly => write!(f, "Weekly"),
+            Interval::Cron(expr) => write!(f, "Cron: {}", expr),
+            Interval::Every(period) => write!(f, "Every {}", format_period(*period)),
+            Interval::At { time, weekdays } => write!(f, "{}", format_at(*time, weekdays)),
+            Interval::Monthly { day, time } => match day {
+                MonthDay::Day(day) => {
+                    write!(f, "Monthly on day {} at {}", day, time.format("%H:%M"))
+                }
+                MonthDay::Last(weekday) => write!(
+                    f,
+                    "Monthly on the last {} at {}",
+                    weekday,
+                    time.format("%H:%M")
+                ),
+                MonthDay::Nth(n, weekday) => write!(
+                    f,
+                    "Monthly on the {} {} at {}",
+                    ORDINALS.get((*n as usize).wrapping_sub(1)).unwrap_or(&"?"),
+                    weekday,
+                    time.format("%H:%M")
+                ),
+            },
+            Interval::Yearly { month, day, time } => write!(
+                f,
+                "Yearly on {} {} at {}",
+                MONTH_ABBREVIATIONS
+                    .get((*month as usize).wrapping_sub(1))
+                    .unwrap_or(&"?"),
+                day,
+                time.format("%H:%M")
+            ),
+            Interval::Once(at) => write!(f, "Once at {}", at.format("%Y-%m-%d %H:%M")),
+        }
+    }
+}
+
+/// Parses the `Display` form of an interval back, e.g. "Every 15 minutes",
+/// "Mon/Wed/Fri at 18:00" or "Cron: 0 9 * * *". Matching is case-insensitive.
+impl FromStr for Interval {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let lower = s.to_ascii_lowercase();
+        let interval = match lower.as_str() {
+            "hourly" => Some(Interval::Hourly),
+            "daily" => Some(Interval::Daily),
+            "weekly" => Some(Interval::Weekly),
+            _ if lower.starts_with("cron:") => return Interval::cron(&s[5..]),
+            _ if lower.starts_with("monthly") => parse_monthly(s),
+            _ if lower.starts_with("yearly") => parse_yearly(s),
+            _ if lower.starts_with("once at") => parse_once(s),
+            _ => parse_period(s).map(Interval::Every).or_else(|| parse_at(s)),
+        };
+        let interval = interval.ok_or_else(|| anyhow!("unrecognized schedule '{}'", s))?;
+        interval.validate()?;
+        Ok(interval)
+    }
+}
+
+/// What to do with scheduled runs that were missed, e.g. while the app was closed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MisfirePolicy {
+    /// Skip every missed run and wait for the next scheduled one.
+    Skip,
+    /// Run once to catch up, however many runs were missed.
+    #[default]
+    RunOnce,
+    /// Run once for every missed occurrence.
+    RunAll,
+}
+
+impl std::fmt::Display for MisfirePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MisfirePolicy::Skip => write!(f, "Skip missed runs"),
+            MisfirePolicy::RunOnce => write!(f, "Run once"),
+            MisfirePolicy::RunAll => write!(f, "Run every missed"),
+        }
+    }
+}
+
+// A run this late is considered missed rather than merely picked up by a slow check.
+const MISFIRE_GRACE_SECS: i64 = 120;
+// Upper bound on the occurrences examined for one catch-up, so a per-second schedule
+// after months of downtime can't stall the check.
+const MAX_CATCH_UP: usize = 1000;
+
+/// What happens to an occurrence that falls on a day its business calendar excludes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HolidayPolicy {
+    /// Drop the occurrence and wait for the next scheduled one.
+    #[default]
+    Skip,
+    /// Run at the same time of day on the next business day.
+    NextBusinessDay,
+}
+
+impl std::fmt::Display for HolidayPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HolidayPolicy::Skip => write!(f, "Skip the run"),
+            HolidayPolicy::NextBusinessDay => write!(f, "Next business day"),
         }
     }
 }
 
+/// The outcome of applying a task's misfire policy at a check, see [`Task::due_runs`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DueRuns {
+    /// Scheduled times to run now, oldest first.
+    pub run: Vec<DateTime<Local>>,
+    /// Missed scheduled times that will not be run, oldest first.
+    pub skipped: Vec<DateTime<Local>>,
+    /// Set when more runs were missed than could be listed.
+    pub truncated: bool,
+    /// Set when the task is due but held back by a blackout window. `run` and
+    /// `skipped` are then empty; the runs are decided once the window ends.
+    pub deferred: Option<Deferral>,
+}
+
+impl DueRuns {
+    /// True if the check has nothing to run, skip or defer.
+    pub fn is_empty(&self) -> bool {
+        self.run.is_empty() && self.skipped.is_empty() && self.deferred.is_none()
+    }
+}
+
+/// Why a due task is being held back, see [`Task::deferral`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deferral {
+    /// When the blackout (including any windows directly following it) ends.
+    pub until: DateTime<Local>,
+    /// The window the task is currently in.
+    pub window: BlackoutWindow,
+    /// True if the window is one of the task's own rather than a global one.
+    pub per_task: bool,
+}
+
+impl std::fmt::Display for Deferral {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let scope = if self.per_task { "task" } else { "global" };
+        write!(f, "{} blackout {}", scope, self.window)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Task {
     pub name: String,
     pub interval: Interval,
     pub last_run: Option<DateTime<Local>>,
     pub enabled: bool,
+    /// IANA time zone that wall-clock schedules are evaluated in. `None` uses the
+    /// system's local time zone.
+    #[serde(default)]
+    pub timezone: Option<Tz>,
+    #[serde(default)]
+    pub misfire: MisfirePolicy,
+    /// Occurrences up to this moment were skipped by the misfire policy. Scheduling
+    /// resumes from the later of this and `last_run`.
+    #[serde(default)]
+    pub skipped_through: Option<DateTime<Local>>,
+    /// Upper bound for a random delay added to each run, so tasks that share a
+    /// schedule don't all start at the same instant.
+    #[serde(default)]
+    pub jitter: Option<Duration>,
+    /// Windows, in the task's time zone, during which the task must not start.
+    /// Runs that fall due inside one are deferred until it ends.
+    #[serde(default)]
+    pub blackouts: Vec<BlackoutWindow>,
+    /// First day, in the task's time zone, on which the task may run.
+    #[serde(default)]
+    pub start_date: Option<NaiveDate>,
+    /// Last day, in the task's time zone, on which the task may run.
+    #[serde(default)]
+    pub end_date: Option<NaiveDate>,
+    /// The task expires after this many runs.
+    #[serde(default)]
+    pub max_runs: Option<u32>,
+    /// Runs performed so far, counted against `max_runs`.
+    #[serde(default)]
+    pub run_count: u32,
+    /// Name of the business calendar, from the app settings, that decides which
+    /// days the task may run on.
+    #[serde(default)]
+    pub calendar: Option<String>,
+    #[serde(default)]
+    pub holiday_policy: HolidayPolicy,
+    /// The calendar named by `calendar`, attached with [`attach_calendars`].
+    #[serde(skip)]
+    pub business_calendar: Option<Arc<BusinessCalendar>>,
+    /// Names of tasks that must complete before each run of this one.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Decides which due task starts first when runs have to queue.
+    #[serde(default)]
+    pub priority: Priority,
+    /// Free-form labels for filtering and enabling or disabling tasks as a group.
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+    /// While set and in the future, the task is treated as disabled; it resumes
+    /// on its own afterwards.
+    #[serde(default)]
+    pub paused_until: Option<DateTime<Local>>,
+    /// The program started on each run. A task without one only records its runs.
+    #[serde(default)]
+    pub action: Option<Action>,
+    /// How the action's most recent run ended.
+    #[serde(default)]
+    pub last_outcome: Option<RunRecord>,
+    /// When the action last exited successfully.
+    #[serde(default)]
+    pub last_success: Option<DateTime<Local>>,
+    /// When the action last failed, exited with an error or could not start.
+    #[serde(default)]
+    pub last_failure: Option<DateTime<Local>>,
 }
 
 impl Task {
@@ -38,30 +617,563 @@ impl Task {
             interval,
             last_run: None,
             enabled: false,
+            timezone: None,
+            misfire: MisfirePolicy::default(),
+            skipped_through: None,
+            jitter: None,
+            blackouts: Vec::new(),
+            start_date: None,
+            end_date: None,
+            max_runs: None,
+            run_count: 0,
+            calendar: None,
+            holiday_policy: HolidayPolicy::default(),
+            business_calendar: None,
+            depends_on: Vec::new(),
+            priority: Priority::default(),
+            tags: BTreeSet::new(),
+            paused_until: None,
+            action: None,
+            last_outcome: None,
+            last_success: None,
+            last_failure: None,
+        }
+    }
+
+    // `t` as a wall-clock time in the task's time zone.
+    fn wall_clock(&self, t: DateTime<Local>) -> NaiveDateTime {
+        match self.timezone {
+            Some(tz) => t.with_timezone(&tz).naive_local(),
+            None => t.naive_local(),
+        }
+    }
+
+    // A wall-clock time in the task's time zone, resolved like scheduled slots.
+    fn resolve_wall_clock(&self, naive: NaiveDateTime) -> Option<DateTime<Local>> {
+        match self.timezone {
+            Some(tz) => resolve_local(&tz, naive).map(|dt| dt.with_timezone(&Local)),
+            None => resolve_local(&Local, naive),
+        }
+    }
+
+    // Midnight at the start of `date` in the task's time zone.
+    fn start_of(&self, date: NaiveDate) -> Option<DateTime<Local>> {
+        self.resolve_wall_clock(date.and_time(NaiveTime::MIN))
+    }
+
+    // Moves an occurrence that falls on a day excluded by the task's business
+    // calendar according to its holiday policy.
+    fn on_business_day(&self, t: DateTime<Local>) -> Option<DateTime<Local>> {
+        let Some(calendar) = &self.business_calendar else {
+            return Some(t);
+        };
+        let mut t = t;
+        // Each pass moves to a later scheduled slot; a calendar that excludes
+        // every slot for a year never runs
+        for _ in 0..366 {
+            let wall = self.wall_clock(t);
+            if calendar.is_business_day(wall.date()) {
+                return Some(t);
+            }
+            let next_day = calendar.next_business_day(wall.date())?;
+            t = match (self.holiday_policy, &self.interval) {
+                (HolidayPolicy::NextBusinessDay, _) => {
+                    return self.resolve_wall_clock(next_day.and_time(wall.time()));
+                }
+                (HolidayPolicy::Skip, Interval::Once(_)) => return None,
+                // Elapsed-time intervals resume when the next business day starts
+                (
+                    HolidayPolicy::Skip,
+                    Interval::Hourly | Interval::Daily | Interval::Weekly | Interval::Every(_),
+                ) => return self.start_of(next_day),
+                (HolidayPolicy::Skip, _) => self.next_scheduled_after(t)?,
+            };
+        }
+        None
+    }
+
+    // The first moment after the end date, from which the task never runs.
+    fn valid_until(&self) -> Option<DateTime<Local>> {
+        self.start_of(self.end_date?.succ_opt()?)
+    }
+
+    fn runs_left(&self) -> Option<u32> {
+        self.max_runs.map(|max| max.saturating_sub(self.run_count))
+    }
+
+    // First calendar slot after `after`, evaluated in the task's time zone.
+    fn next_scheduled_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        match self.timezone {
+            Some(tz) => self
+                .interval
+                .next_after_in(&after.with_timezone(&tz))
+                .map(|next| next.with_timezone(&Local)),
+            None => self.interval.next_after_in(&after),
+        }
+    }
+
+    // When the schedule next falls due after a run at `last`, on a business day.
+    fn due_after_run(&self, last: DateTime<Local>) -> Option<DateTime<Local>> {
+        self.scheduled_after_run(last)
+            .and_then(|next| self.on_business_day(next))
+    }
+
+    // When the schedule next falls due after a run at `last`. Calendar schedules
+    // keep their slot however late the previous check happened. Invalid cron
+    // expressions are rejected on create/load, so they never fall due here.
+    fn scheduled_after_run(&self, last: DateTime<Local>) -> Option<DateTime<Local>> {
+        match &self.interval {
+            // A one-shot task never fires again once it has run
+            Interval::Once(_) => None,
+            Interval::Hourly => Some(last + chrono::Duration::hours(1)),
+            Interval::Daily => Some(last + chrono::Duration::days(1)),
+            Interval::Weekly => Some(last + chrono::Duration::weeks(1)),
+            Interval::Every(period) => chrono::Duration::from_std(*period)
+                .ok()
+                .and_then(|period| last.checked_add_signed(period)),
+            Interval::Cron(_)
+            | Interval::At { .. }
+            | Interval::Monthly { .. }
+            | Interval::Yearly { .. } => self.next_scheduled_after(last),
+        }
+    }
+
+    // When the task falls due, regardless of `enabled`. Tasks that have never run
+    // are due immediately, except one-shot tasks which wait for their target time.
+    // Nothing falls due outside the task's start and end dates or past `max_runs`.
+    fn due_at(&self, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        if self.runs_left() == Some(0) {
+            return None;
+        }
+        let due = match (self.last_run.max(self.skipped_through), &self.interval) {
+            (Some(last), _) => self.due_after_run(last)?,
+            (None, Interval::Once(at)) => *at,
+            (None, _) => now, // Never run before
+        };
+        let due = match self.start_date.and_then(|date| self.start_of(date)) {
+            // Elapsed-time intervals start on the start date; calendar schedules
+            // wait for their first slot on or after it
+            Some(from) if due < from => match &self.interval {
+                Interval::Hourly | Interval::Daily | Interval::Weekly | Interval::Every(_) => from,
+                Interval::Once(_) => return None,
+                _ => self.next_scheduled_after(from - chrono::Duration::seconds(1))?,
+            },
+            _ => due,
+        };
+        let due = self.on_business_day(due)?;
+        match self.valid_until() {
+            Some(until) if due >= until => None,
+            _ => Some(due),
         }
     }
 
     pub fn should_run(&self, now: DateTime<Local>) -> bool {
-        if !self.enabled {
-            return false;
+        self.enabled && !self.is_paused(now) && self.due_at(now).is_some_and(|due| due <= now)
+    }
+
+    /// True while the task is snoozed.
+    pub fn is_paused(&self, now: DateTime<Local>) -> bool {
+        self.paused_until.is_some_and(|until| now < until)
+    }
+
+    /// If `at` falls inside one of the task's blackout windows or one of the
+    /// `global` ones, when the task may start again and which window holds it back.
+    /// Windows are evaluated in the task's time zone.
+    pub fn deferral(&self, global: &[BlackoutWindow], at: DateTime<Local>) -> Option<Deferral> {
+        if self.blackouts.is_empty() && global.is_empty() {
+            return None;
+        }
+        let windows: Vec<BlackoutWindow> = self.blackouts.iter().chain(global).cloned().collect();
+        let (end, window) = blackout_end(&windows, self.wall_clock(at))?;
+        Some(Deferral {
+            until: self.resolve_wall_clock(end)?,
+            window: window.clone(),
+            per_task: self.blackouts.contains(window),
+        })
+    }
+
+    /// Splits the occurrences that fell due up to `now` into runs to perform and
+    /// runs to skip, according to the task's misfire policy. Empty if the task is
+    /// not due; only `deferred` is set if `now` is inside a blackout window, either
+    /// the task's own or one of `global_blackouts`.
+    pub fn due_runs(&self, now: DateTime<Local>, global_blackouts: &[BlackoutWindow]) -> DueRuns {
+        let mut due = DueRuns::default();
+        if !self.should_run(now) {
+            return due;
+        }
+        if let Some(deferral) = self.deferral(global_blackouts, now) {
+            due.deferred = Some(deferral);
+            return due;
+        }
+
+        // Misfire policies see every missed occurrence; `max_runs` caps the runs below
+        let mut occurrences: Vec<_> = Occurrences {
+            task: self,
+            next: self.due_at(now),
+            runs_left: None,
+        }
+        .take_while(|t| *t <= now)
+        .take(MAX_CATCH_UP + 1)
+        .collect();
+        if occurrences.len() > MAX_CATCH_UP {
+            occurrences.pop();
+            due.truncated = true;
         }
 
-        if let Some(last) = self.last_run {
-            match self.interval {
-                Interval::Hourly => now.signed_duration_since(last).num_hours() >= 1,
-                Interval::Daily => now.signed_duration_since(last).num_days() >= 1,
-                Interval::Weekly => now.signed_duration_since(last).num_weeks() >= 1,
+        match self.misfire {
+            MisfirePolicy::RunAll => due.run = occurrences,
+            MisfirePolicy::RunOnce => {
+                due.run.extend(occurrences.pop());
+                due.skipped = occurrences;
             }
+            MisfirePolicy::Skip => {
+                // The latest occurrence still runs if it is only as late as a check,
+                // counting from the end of any blackout or snooze it waited out
+                let latest = occurrences.pop();
+                let on_time = latest.filter(|t| {
+                    let start = self
+                        .deferral(global_blackouts, *t)
+                        .map_or(*t, |deferral| deferral.until)
+                        .max(self.paused_until.unwrap_or(*t));
+                    !due.truncated
+                        && now.signed_duration_since(start).num_seconds() <= MISFIRE_GRACE_SECS
+                });
+                due.skipped = occurrences;
+                match on_time {
+                    Some(t) => due.run.push(t),
+                    None => due.skipped.extend(latest),
+                }
+            }
+        }
+        if let Some(left) = self.runs_left() {
+            due.run.truncate(left as usize);
+        }
+        due
+    }
+
+    /// The earliest moment at or after `now` when `should_run` becomes true, or
+    /// `None` if the task is disabled or will never run again. A task that is
+    /// already due returns `now`.
+    pub fn next_run(&self, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        if !self.enabled {
+            return None;
+        }
+        let resume = self.paused_until.unwrap_or(now).max(now);
+        self.due_at(now).map(|due| due.max(resume))
+    }
+
+    /// Upcoming runs starting with `next_run(now)`, assuming each run happens
+    /// exactly when due. Use `.take(n)` for a preview of the next `n` runs.
+    pub fn upcoming(&self, now: DateTime<Local>) -> Occurrences<'_> {
+        Occurrences {
+            task: self,
+            next: self.next_run(now),
+            runs_left: self.runs_left(),
+        }
+    }
+
+    /// When the scheduler next needs to check the task: its next run, moved to the
+    /// end of any blackout window it falls in. `None` if the task will not run
+    /// again, or if it is due but waiting for upstream tasks in `tasks`, since it is
+    /// only released when one of those runs.
+    pub fn next_wakeup(
+        &self,
+        now: DateTime<Local>,
+        global_blackouts: &[BlackoutWindow],
+        tasks: &[Task],
+    ) -> Option<DateTime<Local>> {
+        self.next_wakeup_in(now, global_blackouts, |name| {
+            tasks.iter().find(|t| t.name == name)
+        })
+    }
+
+    // `next_wakeup` with the upstream tasks looked up by name
+    fn next_wakeup_in<'a>(
+        &self,
+        now: DateTime<Local>,
+        global_blackouts: &[BlackoutWindow],
+        upstream: impl Fn(&str) -> Option<&'a Task>,
+    ) -> Option<DateTime<Local>> {
+        let due = self.next_run(now)?;
+        let at = self
+            .deferral(global_blackouts, due)
+            .map_or(due, |deferral| deferral.until);
+        (at > now || self.blocked_by_in(upstream).is_empty()).then_some(at)
+    }
+
+    /// Records a run that completed at `now`.
+    pub fn mark_complete(&mut self, now: DateTime<Local>) {
+        self.last_run = Some(now);
+        self.run_count = self.run_count.saturating_add(1);
+        // One-shot tasks are done after their run
+        if matches!(self.interval, Interval::Once(_)) {
+            self.enabled = false;
+        }
+    }
+
+    /// Records how a run of the task's action ended.
+    pub fn record_outcome(&mut self, run: RunRecord) {
+        if run.status.is_success() {
+            self.last_success = Some(run.finished);
         } else {
-            true // Never run before
+            self.last_failure = Some(run.finished);
+        }
+        self.last_outcome = Some(run);
+    }
+
+    /// True if the action's most recent run did not succeed.
+    pub fn last_run_failed(&self) -> bool {
+        self.last_outcome
+            .as_ref()
+            .is_some_and(|run| !run.status.is_success())
+    }
+
+    /// Records that every occurrence up to `through` was skipped without running.
+    pub fn mark_skipped(&mut self, through: DateTime<Local>) {
+        self.skipped_through = self.skipped_through.max(Some(through));
+        if matches!(self.interval, Interval::Once(_)) {
+            self.enabled = false;
+        }
+    }
+
+    /// True for a one-shot task that has already fired or been skipped.
+    pub fn is_completed(&self) -> bool {
+        matches!(self.interval, Interval::Once(_))
+            && (self.last_run.is_some() || self.skipped_through.is_some())
+    }
+
+    /// When the task last completed, as the tasks that run after it see it: the
+    /// last successful run of its action, or its last run if it has no action.
+    pub fn last_completed(&self) -> Option<DateTime<Local>> {
+        match self.action {
+            Some(_) => self.last_success,
+            None => self.last_run,
+        }
+    }
+
+    /// Names of the upstream tasks in `tasks` that have not completed since this
+    /// task last ran, so its current cycle has to wait for them. An upstream task
+    /// whose action is still running or failed has not completed. Upstream tasks
+    /// missing from `tasks` always block.
+    pub fn blocked_by(&self, tasks: &[Task]) -> Vec<String> {
+        self.blocked_by_in(|name| tasks.iter().find(|t| t.name == name))
+    }
+
+    /// [`blocked_by`](Task::blocked_by) with the upstream tasks looked up by name,
+    /// for callers that keep their own index of the tasks.
+    pub fn blocked_by_in<'a>(&self, upstream: impl Fn(&str) -> Option<&'a Task>) -> Vec<String> {
+        self.depends_on
+            .iter()
+            .filter(|name| {
+                match upstream(name) {
+                    // Completed within this cycle, i.e. after our own last run
+                    Some(upstream) => match (upstream.last_completed(), self.last_run) {
+                        (Some(done), Some(ours)) => done <= ours,
+                        (Some(_), None) => false,
+                        (None, _) => true,
+                    },
+                    None => true,
+                }
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// True once the task is past its end date or has used up `max_runs`.
+    pub fn is_expired(&self, now: DateTime<Local>) -> bool {
+        self.runs_left() == Some(0) || self.valid_until().is_some_and(|until| now >= until)
+    }
+}
+
+/// How long to wait before starting each of a batch of tasks that fell due on the
+/// same check, in the same order as `tasks`.
+///
+/// Each task gets a uniformly random delay up to its own `jitter`. With a spread
+/// window, the batch is additionally assigned evenly spaced slots across the window
+/// in random order. Pass a seeded RNG for reproducible delays.
+pub fn start_delays<R: Rng>(
+    tasks: &[&Task],
+    spread_window: Option<Duration>,
+    rng: &mut R,
+) -> Vec<Duration> {
+    let mut slots: Vec<Duration> = match spread_window {
+        Some(window) if tasks.len() > 1 => (0..tasks.len())
+            .map(|i| window.mul_f64(i as f64 / tasks.len() as f64))
+            .collect(),
+        _ => vec![Duration::ZERO; tasks.len()],
+    };
+    slots.shuffle(rng);
+
+    tasks
+        .iter()
+        .zip(slots)
+        .map(|(task, slot)| {
+            let jitter = match task.jitter {
+                Some(max) if !max.is_zero() => max.mul_f64(rng.gen_range(0.0..=1.0)),
+                _ => Duration::ZERO,
+            };
+            slot + jitter
+        })
+        .collect()
+}
+
+/// Attaches each task's named business calendar from `calendars`, so scheduling
+/// honours it. Fails, after attaching the rest, if a task names a calendar that
+/// doesn't exist; those tasks run without one.
+pub fn attach_calendars(
+    tasks: &mut [Task],
+    calendars: &[BusinessCalendar],
+) -> Result<(), anyhow::Error> {
+    let mut missing = Vec::new();
+    for task in tasks.iter_mut() {
+        task.business_calendar = None;
+        let Some(name) = &task.calendar else {
+            continue;
+        };
+        match calendars.iter().find(|c| &c.name == name) {
+            Some(calendar) => task.business_calendar = Some(Arc::new(calendar.clone())),
+            None => missing.push(format!("task '{}': unknown calendar '{}'", task.name, name)),
+        }
+    }
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!(missing.join("; ")))
+    }
+}
+
+/// Every tag used by any of `tasks`, sorted.
+pub fn all_tags(tasks: &[Task]) -> BTreeSet<String> {
+    tasks.iter().flat_map(|t| t.tags.iter().cloned()).collect()
+}
+
+/// Enables or disables every task carrying `tag` and returns how many changed.
+pub fn set_tag_enabled(tasks: &mut [Task], tag: &str, enabled: bool) -> usize {
+    let mut changed = 0;
+    for task in tasks.iter_mut().filter(|t| t.tags.contains(tag)) {
+        if task.enabled != enabled {
+            task.enabled = enabled;
+            changed += 1;
+        }
+    }
+    changed
+}
+
+/// Checks that no two tasks share a name, since tasks are looked up by name.
+pub fn validate_task_names(tasks: &[Task]) -> Result<(), anyhow::Error> {
+    let mut seen = std::collections::HashSet::new();
+    match tasks.iter().find(|t| !seen.insert(t.name.as_str())) {
+        Some(task) => Err(anyhow!("more than one task is named '{}'", task.name)),
+        None => Ok(()),
+    }
+}
+
+/// Checks `task` before it joins the tasks found by `existing`, looked up by
+/// name: no task may already have its name, and every task it runs after must
+/// exist. This costs a lookup per dependency; cycles are left to
+/// [`validate_dependencies`].
+pub fn validate_new_task<'a>(
+    task: &Task,
+    existing: impl Fn(&str) -> Option<&'a Task>,
+) -> Result<(), anyhow::Error> {
+    if existing(&task.name).is_some() {
+        return Err(anyhow!("a task named '{}' already exists", task.name));
+    }
+    for upstream in &task.depends_on {
+        if *upstream == task.name {
+            return Err(anyhow!("task '{}' can't run after itself", task.name));
+        }
+        if existing(upstream).is_none() {
+            return Err(anyhow!(
+                "task '{}' depends on unknown task '{}'",
+                task.name,
+                upstream
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that every dependency names an existing task and that the dependencies
+/// contain no cycle, reporting the first cycle found as "A -> B -> A".
+pub fn validate_dependencies(tasks: &[Task]) -> Result<(), anyhow::Error> {
+    let by_name: std::collections::HashMap<&str, &Task> =
+        tasks.iter().map(|t| (t.name.as_str(), t)).collect();
+    for task in tasks {
+        for upstream in &task.depends_on {
+            if !by_name.contains_key(upstream.as_str()) {
+                return Err(anyhow!(
+                    "task '{}' depends on unknown task '{}'",
+                    task.name,
+                    upstream
+                ));
+            }
+        }
+    }
+
+    // Depth-first search; a task reached again while still on the path closes a cycle
+    fn visit<'a>(
+        by_name: &std::collections::HashMap<&'a str, &'a Task>,
+        name: &'a str,
+        path: &mut Vec<&'a str>,
+        done: &mut std::collections::HashSet<&'a str>,
+    ) -> Result<(), anyhow::Error> {
+        if let Some(start) = path.iter().position(|n| *n == name) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(name);
+            return Err(anyhow!("dependency cycle: {}", cycle.join(" -> ")));
+        }
+        if !done.insert(name) {
+            return Ok(());
+        }
+        path.push(name);
+        if let Some(task) = by_name.get(name) {
+            for upstream in &task.depends_on {
+                visit(by_name, upstream, path, done)?;
+            }
         }
+        path.pop();
+        Ok(())
+    }
+
+    let mut done = std::collections::HashSet::new();
+    for task in tasks {
+        visit(&by_name, &task.name, &mut Vec::new(), &mut done)?;
     }
+    Ok(())
+}
+
+/// Iterator over a task's upcoming runs, returned by [`Task::upcoming`].
+pub struct Occurrences<'a> {
+    task: &'a Task,
+    next: Option<DateTime<Local>>,
+    runs_left: Option<u32>,
+}
 
-    pub fn mark_complete(&mut self) {
-        self.last_run = Some(Local::now());
+impl Iterator for Occurrences<'_> {
+    type Item = DateTime<Local>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next?;
+        if self
+            .task
+            .valid_until()
+            .is_some_and(|until| current >= until)
+        {
+            return None;
+        }
+        match &mut self.runs_left {
+            Some(0) => return None,
+            Some(left) => *left -= 1,
+            None => {}
+        }
+        self.next = self.task.due_after_run(current);
+        Some(current)
     }
 }
 
+/// Where `tasks.json` is kept. Every other file the app writes (`settings.json`,
+/// run output) goes in the same directory, so the override below moves them all.
 pub fn project_file_path() -> Option<PathBuf> {
     // Allow tests or users to override the config directory via an env var for isolation.
     if let Ok(override_dir) = std::env::var("TASK_SCHEDULER_CONFIG_DIR") {
@@ -130,24 +1242,8 @@ pub async fn save_tasks(tasks: &[Task]) -> Result<(), anyhow::Error> {
             merged_tasks.push(task.clone());
         }
     }
-
-    let json = serde_json::to_string_pretty(&merged_tasks).context("serializing tasks")?;
-
-    // Use a unique temp filename to avoid conflicts in concurrent writes
-    let mut rng = rand::thread_rng();
-    let tmp = path.with_extension(format!("tmp.{}", rng.gen::<u64>()));
-
-    // Write to temp file first
-    fs::write(&tmp, &json).with_context(|| format!("writing temp file {}", tmp.display()))?;
-
-    // Use atomic rename for safe concurrent access
-    if let Err(e) = fs::rename(&tmp, &path) {
-        // Clean up temp file on error
-        let _ = fs::remove_file(&tmp);
-        return Err(e).with_context(|| {
-            format!("renaming temp file {} to {}", tmp.display(), path.display())
-        });
-    }
+    validate_dependencies(&merged_tasks)?;
+    write_tasks_file(&path, &merged_tasks)?;
 
     // Remove any stray temp files that might be left from failed previous attempts
     if let Some(parent) = path.parent() {
@@ -168,6 +1264,42 @@ pub async fn save_tasks(tasks: &[Task]) -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+/// Saves `tasks` as the complete task list, so tasks missing from it are removed
+/// from tasks.json, unlike [`save_tasks`] which merges with the stored tasks. The
+/// list is checked like a loaded one first.
+pub async fn replace_tasks(tasks: &[Task]) -> Result<(), anyhow::Error> {
+    let path = project_file_path().ok_or_else(|| anyhow!("no config dir"))?;
+    validate_task_names(tasks)?;
+    validate_dependencies(tasks)?;
+    write_tasks_file(&path, tasks)
+}
+
+// Writes `tasks` to `path` atomically through a temp file unique to this call, so
+// overlapping saves never write into each other's temp file
+fn write_tasks_file(path: &Path, tasks: &[Task]) -> Result<(), anyhow::Error> {
+    let json = serde_json::to_string_pretty(tasks).context("serializing tasks")?;
+
+    // Use a unique temp filename to avoid conflicts in concurrent writes
+    let mut rng = rand::thread_rng();
+    let tmp = path.with_extension(format!("tmp.{}", rng.gen::<u64>()));
+
+    // Write to temp file first
+    if let Err(e) = fs::write(&tmp, &json) {
+        let _ = fs::remove_file(&tmp);
+        return Err(e).with_context(|| format!("writing temp file {}", tmp.display()));
+    }
+
+    // Use atomic rename for safe concurrent access
+    if let Err(e) = fs::rename(&tmp, path) {
+        // Clean up temp file on error
+        let _ = fs::remove_file(&tmp);
+        return Err(e).with_context(|| {
+            format!("renaming temp file {} to {}", tmp.display(), path.display())
+        });
+    }
+    Ok(())
+}
+
 pub async fn load_tasks() -> Result<Vec<Task>, anyhow::Error> {
     let path = project_file_path().ok_or_else(|| anyhow!("no config dir"))?;
     if !path.exists() {
@@ -177,13 +1309,32 @@ pub async fn load_tasks() -> Result<Vec<Task>, anyhow::Error> {
     let data = fs::read_to_string(&path)
         .with_context(|| format!("reading tasks file {}", path.display()))?;
 
+    parse_tasks(&data).with_context(|| format!("loading tasks file {}", path.display()))
+}
+
+/// Parses the contents of tasks.json, falling back to a lenient field-by-field
+/// reader for older or hand-edited files. Every task's interval is validated, and
+/// task names must be unique.
+pub fn parse_tasks(data: &str) -> Result<Vec<Task>, anyhow::Error> {
     // Try direct deserialization first
-    if let Ok(list) = serde_json::from_str::<Vec<Task>>(&data) {
-        return Ok(list);
+    let tasks = match serde_json::from_str::<Vec<Task>>(data) {
+        Ok(list) => list,
+        Err(_) => parse_tasks_lenient(data)?,
+    };
+
+    for task in &tasks {
+        task.interval
+            .validate()
+            .with_context(|| format!("task '{}'", task.name))?;
     }
+    validate_task_names(&tasks)?;
 
-    // Fallback: manual parsing for backward compatibility
-    let v: serde_json::Value = serde_json::from_str(&data).context("parsing tasks JSON")?;
+    Ok(tasks)
+}
+
+// Fallback: manual parsing for backward compatibility
+fn parse_tasks_lenient(data: &str) -> Result<Vec<Task>, anyhow::Error> {
+    let v: serde_json::Value = serde_json::from_str(data).context("parsing tasks JSON")?;
     let arr = v.as_array().ok_or_else(|| anyhow!("expected array"))?;
 
     let mut out = Vec::with_capacity(arr.len());
@@ -194,11 +1345,7 @@ pub async fn load_tasks() -> Result<Vec<Task>, anyhow::Error> {
             .unwrap_or("")
             .to_string();
 
-        let interval = match item.get("interval").and_then(|s| s.as_str()) {
-            Some("Hourly") | Some("hourly") => Interval::Hourly,
-            Some("Weekly") | Some("weekly") => Interval::Weekly,
-            _ => Interval::Daily,
-        };
+        let interval = parse_interval_lenient(item.get("interval"));
 
         let enabled = item
             .get("enabled")
@@ -217,17 +1364,185 @@ pub async fn load_tasks() -> Result<Vec<Task>, anyhow::Error> {
             }
         });
 
+        // An unknown zone is an error rather than a silent switch to local time
+        let timezone = match item.get("timezone").and_then(|tz| tz.as_str()) {
+            Some(tz) => Some(
+                tz.parse::<Tz>()
+                    .map_err(|e| anyhow!("task '{}': unknown time zone: {}", name, e))?,
+            ),
+            None => None,
+        };
+
+        let misfire = item
+            .get("misfire")
+            .and_then(|m| serde_json::from_value(m.clone()).ok())
+            .unwrap_or_default();
+
+        let skipped_through = item
+            .get("skipped_through")
+            .and_then(|s| s.as_str())
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|dt| dt.with_timezone(&Local));
+
+        // Serde's {"secs": N, "nanos": 0} or a bare number of seconds
+        let jitter = item.get("jitter").and_then(|j| {
+            j.get("secs")
+                .and_then(|s| s.as_u64())
+                .or_else(|| j.as_u64())
+                .map(Duration::from_secs)
+        });
+
+        // Serde's window objects or the "22:00-07:00, weekends" text form
+        let blackouts = match item.get("blackouts") {
+            Some(serde_json::Value::String(s)) => parse_blackouts(s)
+                .with_context(|| format!("task '{}': invalid blackout windows", name))?,
+            Some(b) => serde_json::from_value(b.clone()).unwrap_or_default(),
+            None => Vec::new(),
+        };
+
+        let date = |key: &str| {
+            item.get(key)
+                .and_then(|d| d.as_str())
+                .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        };
+        let start_date = date("start_date");
+        let end_date = date("end_date");
+        let max_runs = item
+            .get("max_runs")
+            .and_then(|m| m.as_u64())
+            .map(|m| m.min(u32::MAX as u64) as u32);
+        let run_count = item
+            .get("run_count")
+            .and_then(|c| c.as_u64())
+            .map_or(0, |c| c.min(u32::MAX as u64) as u32);
+
+        let calendar = item
+            .get("calendar")
+            .and_then(|c| c.as_str())
+            .map(str::to_string);
+        let depends_on = item
+            .get("depends_on")
+            .and_then(|d| d.as_array())
+            .map(|names| {
+                names
+                    .iter()
+                    .filter_map(|n| n.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let paused_until = item
+            .get("paused_until")
+            .and_then(|p| p.as_str())
+            .and_then(|p| DateTime::parse_from_rfc3339(p).ok())
+            .map(|dt| dt.with_timezone(&Local));
+        let action = item
+            .get("action")
+            .and_then(|a| serde_json::from_value(a.clone()).ok());
+        let last_outcome = item
+            .get("last_outcome")
+            .and_then(|o| serde_json::from_value(o.clone()).ok());
+        let timestamp = |key: &str| {
+            item.get(key)
+                .and_then(|t| t.as_str())
+                .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                .map(|dt| dt.with_timezone(&Local))
+        };
+        let tags = item
+            .get("tags")
+            .and_then(|t| t.as_array())
+            .map(|tags| {
+                tags.iter()
+                    .filter_map(|t| t.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let priority = item
+            .get("priority")
+            .and_then(|p| serde_json::from_value(p.clone()).ok())
+            .unwrap_or_default();
+        let holiday_policy = item
+            .get("holiday_policy")
+            .and_then(|p| serde_json::from_value(p.clone()).ok())
+            .unwrap_or_default();
+
         out.push(Task {
             name,
             interval,
             last_run,
             enabled,
+            timezone,
+            misfire,
+            skipped_through,
+            jitter,
+            blackouts,
+            start_date,
+            end_date,
+            max_runs,
+            run_count,
+            calendar,
+            holiday_policy,
+            business_calendar: None,
+            depends_on,
+            priority,
+            tags,
+            paused_until,
+            action,
+            last_outcome,
+            last_success: timestamp("last_success"),
+            last_failure: timestamp("last_failure"),
         });
     }
 
     Ok(out)
 }
 
+fn parse_interval_lenient(value: Option<&serde_json::Value>) -> Interval {
+    let Some(value) = value else {
+        return Interval::Daily;
+    };
+
+    if let Some(obj) = value.as_object() {
+        // Any interval exactly as written by serde
+        if let Ok(interval) = serde_json::from_value::<Interval>(value.clone()) {
+            return interval;
+        }
+
+        // {"Cron": "..."} as written by serde, or {"cron": "..."}
+        if let Some(expr) = obj
+            .get("Cron")
+            .or_else(|| obj.get("cron"))
+            .and_then(|e| e.as_str())
+        {
+            return Interval::Cron(expr.trim().to_string());
+        }
+
+        // {"Every": {"secs": N, "nanos": 0}} as written by serde, a bare number of
+        // seconds, or a phrase such as {"every": "15 minutes"}
+        if let Some(every) = obj.get("Every").or_else(|| obj.get("every")) {
+            let period = every
+                .get("secs")
+                .and_then(|s| s.as_u64())
+                .or_else(|| every.as_u64())
+                .map(Duration::from_secs)
+                .or_else(|| every.as_str().and_then(parse_period));
+            if let Some(period) = period {
+                return Interval::Every(period);
+            }
+        }
+    }
+
+    // Display strings such as "Weekly" or "Every 15 minutes"; a cron string that
+    // does not parse is kept so validation reports it instead of silently
+    // becoming Daily.
+    match value.as_str() {
+        Some(s) if s.trim().to_ascii_lowercase().starts_with("cron:") => {
+            Interval::Cron(s.trim()[5..].trim().to_string())
+        }
+        Some(s) => s.parse().unwrap_or(Interval::Daily),
+        None => Interval::Daily,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -292,18 +1607,112 @@ mod tests {
         assert!(!task.should_run(now));
     }
 
+    fn naive(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_monthly_interval_clamps_to_month_end() {
+        let interval = Interval::Monthly {
+            day: MonthDay::Day(31),
+            time: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+        };
+        let next = |s| interval.next_wall_clock_after(naive(s)).unwrap();
+
+        // Leap-year February, common-year February, and a 30-day month
+        assert_eq!(next("2024-01-31 09:00:00"), naive("2024-02-29 09:00:00"));
+        assert_eq!(next("2023-01-31 09:00:00"), naive("2023-02-28 09:00:00"));
+        assert_eq!(next("2024-03-31 09:00:00"), naive("2024-04-30 09:00:00"));
+        // Clamping does not stick: March runs on the 31st again
+        assert_eq!(next("2024-02-29 09:00:00"), naive("2024-03-31 09:00:00"));
+    }
+
+    #[test]
+    fn test_monthly_last_weekday() {
+        let interval = Interval::Monthly {
+            day: MonthDay::Last(Weekday::Fri),
+            time: NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+        };
+        assert_eq!(interval.to_string(), "Monthly on the last Fri at 17:00");
+        assert_eq!(
+            interval.next_wall_clock_after(naive("2024-03-01 00:00:00")),
+            Some(naive("2024-03-29 17:00:00"))
+        );
+        // Past this month's last Friday, so next month's (2024-05-31 is a Friday)
+        assert_eq!(
+            interval.next_wall_clock_after(naive("2024-04-26 17:00:00")),
+            Some(naive("2024-05-31 17:00:00"))
+        );
+        assert_eq!(interval.to_string().parse::<Interval>().unwrap(), interval);
+    }
+
+    #[test]
+    fn test_yearly_interval_leap_day() {
+        let interval = Interval::Yearly {
+            month: 2,
+            day: 29,
+            time: NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+        };
+        assert!(interval.validate().is_ok());
+        assert_eq!(
+            interval.next_wall_clock_after(naive("2024-02-29 08:00:00")),
+            Some(naive("2025-02-28 08:00:00"))
+        );
+        assert_eq!(
+            interval.next_wall_clock_after(naive("2027-03-01 00:00:00")),
+            Some(naive("2028-02-29 08:00:00"))
+        );
+        assert_eq!(interval.to_string(), "Yearly on Feb 29 at 08:00");
+        assert_eq!(interval.to_string().parse::<Interval>().unwrap(), interval);
+
+        let invalid = Interval::Yearly {
+            month: 4,
+            day: 31,
+            time: NaiveTime::MIN,
+        };
+        assert!(invalid.validate().is_err());
+    }
+
     #[test]
     fn test_mark_complete() {
+        let clock = ManualClock::new(Local.with_ymd_and_hms(2024, 5, 8, 9, 0, 0).unwrap());
         let mut task = Task::new("Test", Interval::Daily);
+        task.enabled = true;
         assert!(task.last_run.is_none());
 
-        task.mark_complete();
-        assert!(task.last_run.is_some());
+        task.mark_complete(clock.now());
+        assert_eq!(task.last_run, Some(clock.now()));
 
-        let time_diff = Local::now()
-            .signed_duration_since(task.last_run.unwrap())
-            .num_seconds();
-        assert!(time_diff < 1); // Marked just now
+        clock.advance(Duration::hours(23));
+        assert!(!task.should_run(clock.now()));
+        clock.advance(Duration::hours(1));
+        assert!(task.should_run(clock.now()), "Due exactly a day later");
+    }
+
+    #[test]
+    fn test_record_outcome() {
+        let at = |h| Local.with_ymd_and_hms(2024, 5, 8, h, 0, 0).unwrap();
+        let run = |h, status| RunRecord {
+            started: at(h),
+            finished: at(h) + Duration::seconds(30),
+            status,
+            stdout: None,
+            stderr: None,
+            output_error: None,
+        };
+        let mut task = Task::new("Backup", Interval::Hourly);
+        assert!(!task.last_run_failed());
+
+        task.record_outcome(run(9, RunStatus::Exited(0)));
+        task.record_outcome(run(10, RunStatus::Exited(2)));
+        assert!(task.last_run_failed());
+        assert_eq!(task.last_success, Some(at(9) + Duration::seconds(30)));
+        assert_eq!(task.last_failure, Some(at(10) + Duration::seconds(30)));
+
+        let json = serde_json::to_string(&vec![task.clone()]).unwrap();
+        let decoded = parse_tasks(&json).unwrap();
+        assert_eq!(decoded[0].last_outcome, task.last_outcome);
+        assert_eq!(decoded[0].last_failure, task.last_failure);
     }
 
     #[test]
@@ -354,4 +1763,756 @@ mod tests {
             "Tasks with future last_run should not run"
         );
     }
+
+    #[test]
+    fn test_cron_interval() {
+        let mut task = Task::new("Cron", Interval::cron("0 * * * *").unwrap());
+        task.enabled = true;
+
+        let last = Local.with_ymd_and_hms(2024, 3, 10, 10, 15, 0).unwrap();
+        task.last_run = Some(last);
+        assert!(!task.should_run(last + Duration::minutes(30)));
+        assert!(task.should_run(last + Duration::minutes(45)));
+    }
+
+    #[test]
+    fn test_invalid_cron_rejected() {
+        assert!(Interval::cron("not a cron").is_err());
+
+        let json =
+            r#"[{"name":"Bad","interval":{"Cron":"61 * * * *"},"last_run":null,"enabled":true}]"#;
+        let err = parse_tasks(json).unwrap_err();
+        assert!(format!("{:#}", err).contains("Bad"));
+    }
+
+    #[test]
+    fn test_duplicate_task_names_rejected() {
+        let json = r#"[{"name":"Backup","interval":"Daily"},
+                       {"name":"Report","interval":"Hourly"},
+                       {"name":"Backup","interval":"Hourly"}]"#;
+        let err = parse_tasks(json).unwrap_err();
+        assert!(format!("{:#}", err).contains("more than one task is named 'Backup'"));
+
+        let tasks = parse_tasks(&json.replacen("Backup", "Archive", 1)).unwrap();
+        let find = |name: &str| tasks.iter().find(|t| t.name == name);
+        assert!(validate_new_task(&Task::new("Cleanup", Interval::Daily), find).is_ok());
+        let err = validate_new_task(&Task::new("Report", Interval::Daily), find).unwrap_err();
+        assert_eq!(err.to_string(), "a task named 'Report' already exists");
+    }
+
+    #[test]
+    fn test_new_task_dependencies_checked() {
+        let tasks = [Task::new("Extract", Interval::Daily)];
+        let find = |name: &str| tasks.iter().find(|t| t.name == name);
+        let mut load = Task::new("Load", Interval::Daily);
+        load.depends_on = vec!["Extract".into()];
+        assert!(validate_new_task(&load, find).is_ok());
+
+        load.depends_on.push("Transform".into());
+        let err = validate_new_task(&load, find).unwrap_err();
+        assert!(err.to_string().contains("unknown task 'Transform'"));
+        load.depends_on = vec!["Load".into()];
+        assert!(validate_new_task(&load, find).is_err());
+    }
+
+    #[test]
+    fn test_lenient_cron_interval() {
+        let json = r#"[{"name":"Legacy","interval":"cron: */5 * * * *"}]"#;
+        let tasks = parse_tasks(json).unwrap();
+        assert_eq!(tasks[0].interval, Interval::Cron("*/5 * * * *".into()));
+    }
+
+    #[test]
+    fn test_lenient_tags() {
+        let json = r#"[{"name":"Old","interval":"Daily"},
+                       {"name":"Tagged","interval":"Daily","tags":["ops", 3, "nightly"]}]"#;
+        let tasks = parse_tasks(json).unwrap();
+        assert!(tasks[0].tags.is_empty());
+        assert_eq!(tasks[1].tags.len(), 2);
+        assert!(tasks[1].tags.contains("nightly"));
+    }
+
+    #[test]
+    fn test_every_interval() {
+        let now = Local::now();
+        let mut task = Task::new(
+            "Every",
+            Interval::Every(std::time::Duration::from_secs(900)),
+        );
+        task.enabled = true;
+
+        task.last_run = Some(now - Duration::minutes(16));
+        assert!(task.should_run(now));
+
+        task.last_run = Some(now - Duration::minutes(14));
+        assert!(!task.should_run(now));
+
+        assert!(Interval::Every(std::time::Duration::ZERO)
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_every_interval_display() {
+        let every = |secs| Interval::Every(std::time::Duration::from_secs(secs)).to_string();
+        assert_eq!(every(15 * 60), "Every 15 minutes");
+        assert_eq!(every(3 * 86400), "Every 3 days");
+        assert_eq!(every(3600), "Every 1 hour");
+        assert_eq!(every(90), "Every 90 seconds");
+    }
+
+    #[test]
+    fn test_every_interval_round_trip() {
+        let mut task = Task::new(
+            "Every",
+            Interval::Every(std::time::Duration::from_secs(900)),
+        );
+        task.enabled = true;
+        let json = serde_json::to_string(&vec![task]).unwrap();
+        let decoded = parse_tasks(&json).unwrap();
+        assert_eq!(
+            decoded[0].interval,
+            Interval::Every(std::time::Duration::from_secs(900))
+        );
+
+        // The lenient fallback accepts serde's shape, bare seconds and display strings
+        let json = r#"[
+            {"name":"A","interval":{"Every":{"secs":60}}},
+            {"name":"B","interval":{"every":7200}},
+            {"name":"C","interval":"Every 3 days"}
+        ]"#;
+        let decoded = parse_tasks(json).unwrap();
+        assert_eq!(
+            decoded[0].interval,
+            Interval::Every(std::time::Duration::from_secs(60))
+        );
+        assert_eq!(
+            decoded[1].interval,
+            Interval::Every(std::time::Duration::from_secs(7200))
+        );
+        assert_eq!(
+            decoded[2].interval,
+            Interval::Every(std::time::Duration::from_secs(3 * 86400))
+        );
+    }
+
+    #[test]
+    fn test_at_interval_does_not_drift() {
+        let mut task = Task::new(
+            "Standup",
+            Interval::At {
+                time: NaiveTime::from_hms_opt(9, 30, 0).unwrap(),
+                weekdays: vec![],
+            },
+        );
+        task.enabled = true;
+
+        // Yesterday's run was checked late, at 09:31:45; today's slot is still 09:30
+        task.last_run = Some(Local.with_ymd_and_hms(2024, 5, 6, 9, 31, 45).unwrap());
+        let before = Local.with_ymd_and_hms(2024, 5, 7, 9, 29, 59).unwrap();
+        let at = Local.with_ymd_and_hms(2024, 5, 7, 9, 30, 0).unwrap();
+        assert!(!task.should_run(before));
+        assert!(task.should_run(at));
+    }
+
+    #[test]
+    fn test_at_interval_weekdays() {
+        let interval = Interval::At {
+            time: NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+            weekdays: vec![Weekday::Mon, Weekday::Wed, Weekday::Fri],
+        };
+        assert_eq!(interval.to_string(), "Mon/Wed/Fri at 18:00");
+
+        // 2024-05-08 is a Wednesday; the next slot after its run is Friday
+        let after =
+            NaiveDateTime::parse_from_str("2024-05-08 18:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let next = interval.next_wall_clock_after(after).unwrap();
+        assert_eq!(next.to_string(), "2024-05-10 18:00:00");
+
+        let json = r#"[{"name":"Gym","interval":"Mon/Wed/Fri at 18:00"}]"#;
+        assert_eq!(parse_tasks(json).unwrap()[0].interval, interval);
+    }
+
+    #[test]
+    fn test_once_interval_fires_once() {
+        let now = Local::now();
+        let mut task = Task::new("Release", Interval::Once(now + Duration::minutes(5)));
+        task.enabled = true;
+
+        assert!(!task.should_run(now));
+        assert!(task.should_run(now + Duration::minutes(5)));
+
+        task.mark_complete(now);
+        assert!(
+            !task.enabled,
+            "One-shot tasks disable themselves after running"
+        );
+        assert!(task.is_completed());
+
+        // Even if re-enabled it does not fire a second time
+        task.enabled = true;
+        assert!(!task.should_run(now + Duration::days(1)));
+    }
+
+    #[test]
+    fn test_once_interval_round_trip() {
+        let at = Local.with_ymd_and_hms(2030, 1, 2, 3, 4, 0).unwrap();
+        let mut task = Task::new("Later", Interval::Once(at));
+        task.enabled = true;
+        let json = serde_json::to_string(&vec![task]).unwrap();
+        let decoded = parse_tasks(&json).unwrap();
+        assert_eq!(decoded[0].interval, Interval::Once(at));
+        assert!(!decoded[0].is_completed());
+
+        assert_eq!(
+            "Once at 2030-01-02 03:04".parse::<Interval>().unwrap(),
+            Interval::Once(at)
+        );
+    }
+
+    fn berlin(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Tz> {
+        chrono_tz::Europe::Berlin
+            .with_ymd_and_hms(y, m, d, h, min, 0)
+            .earliest()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_task_timezone_used_for_wall_clock() {
+        let mut task = Task::new(
+            "Tokyo standup",
+            Interval::At {
+                time: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+                weekdays: vec![],
+            },
+        );
+        task.enabled = true;
+        task.timezone = Some(chrono_tz::Asia::Tokyo);
+
+        let tokyo = |d, h| {
+            chrono_tz::Asia::Tokyo
+                .with_ymd_and_hms(2024, 6, d, h, 0, 0)
+                .unwrap()
+                .with_timezone(&Local)
+        };
+        task.last_run = Some(tokyo(3, 9));
+        assert!(!task.should_run(tokyo(4, 8)));
+        assert!(task.should_run(tokyo(4, 9)));
+    }
+
+    #[test]
+    fn test_spring_forward_gap_runs_once_after_jump() {
+        // Berlin skips 02:00-03:00 on 2024-03-31
+        let interval = Interval::At {
+            time: NaiveTime::from_hms_opt(2, 30, 0).unwrap(),
+            weekdays: vec![],
+        };
+        let next = interval.next_after_in(&berlin(2024, 3, 30, 2, 30)).unwrap();
+        assert_eq!(next, berlin(2024, 3, 31, 3, 30));
+
+        // The following day is back to normal
+        let next = interval.next_after_in(&next).unwrap();
+        assert_eq!(next, berlin(2024, 4, 1, 2, 30));
+    }
+
+    #[test]
+    fn test_fall_back_overlap_does_not_double_fire() {
+        // Berlin repeats 02:00-03:00 on 2024-10-27
+        let interval = Interval::cron("30 2 * * *").unwrap();
+        let first = interval
+            .next_after_in(&berlin(2024, 10, 26, 12, 0))
+            .unwrap();
+        assert_eq!(first.naive_local().to_string(), "2024-10-27 02:30:00");
+        assert_eq!(first.offset().fix().local_minus_utc(), 2 * 3600);
+
+        // After the first 02:30 (CEST) the next run is the following day, not 02:30 CET
+        let next = interval.next_after_in(&first).unwrap();
+        assert_eq!(next.naive_local().to_string(), "2024-10-28 02:30:00");
+
+        // Even if the last run happened during the repeated hour
+        let second_pass = first + chrono::Duration::minutes(50);
+        assert_eq!(second_pass.naive_local().to_string(), "2024-10-27 02:20:00");
+        let next = interval.next_after_in(&second_pass).unwrap();
+        assert_eq!(next.naive_local().to_string(), "2024-10-28 02:30:00");
+    }
+
+    #[test]
+    fn test_timezone_round_trip() {
+        let mut task = Task::new("Zoned", Interval::Daily);
+        task.timezone = Some(chrono_tz::America::New_York);
+        let json = serde_json::to_string(&vec![task]).unwrap();
+        assert!(json.contains("America/New_York"));
+        let decoded = parse_tasks(&json).unwrap();
+        assert_eq!(decoded[0].timezone, Some(chrono_tz::America::New_York));
+
+        // Older files without the field load with the local zone
+        let json = r#"[{"name":"Old","interval":"Daily","last_run":null,"enabled":true}]"#;
+        assert_eq!(parse_tasks(json).unwrap()[0].timezone, None);
+
+        let json = r#"[{"name":"Bad","interval":"Daily","timezone":"Mars/Olympus"}]"#;
+        assert!(parse_tasks(json).is_err());
+    }
+
+    #[test]
+    fn test_next_run() {
+        let now = Local::now();
+        let mut task = Task::new("Next", Interval::Hourly);
+        assert_eq!(task.next_run(now), None, "Disabled tasks have no next run");
+
+        task.enabled = true;
+        assert_eq!(task.next_run(now), Some(now), "Never-run tasks are due now");
+
+        task.last_run = Some(now - Duration::minutes(20));
+        assert_eq!(task.next_run(now), Some(now + Duration::minutes(40)));
+        assert!(!task.should_run(now + Duration::minutes(39)));
+        assert!(task.should_run(now + Duration::minutes(40)));
+
+        task.last_run = Some(now - Duration::hours(3));
+        assert_eq!(task.next_run(now), Some(now), "Overdue tasks are due now");
+
+        let mut once = Task::new("Once", Interval::Once(now + Duration::days(2)));
+        once.enabled = true;
+        assert_eq!(once.next_run(now), Some(now + Duration::days(2)));
+        once.mark_complete(now);
+        assert_eq!(once.next_run(now), None);
+    }
+
+    #[test]
+    fn test_upcoming_occurrences() {
+        let mut task = Task::new(
+            "Weekdays",
+            Interval::At {
+                time: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+                weekdays: vec![Weekday::Mon, Weekday::Fri],
+            },
+        );
+        task.enabled = true;
+        task.timezone = Some(chrono_tz::UTC);
+        // 2024-05-06 is a Monday
+        let utc = |d, h| Utc.with_ymd_and_hms(2024, 5, d, h, 0, 0).unwrap();
+        task.last_run = Some(utc(6, 9).with_timezone(&Local));
+
+        let upcoming: Vec<_> = task
+            .upcoming(utc(6, 12).with_timezone(&Local))
+            .take(3)
+            .map(|dt| dt.with_timezone(&Utc))
+            .collect();
+        assert_eq!(upcoming, vec![utc(10, 9), utc(13, 9), utc(17, 9)]);
+
+        let once = Task::new("Once", Interval::Once(Local::now()));
+        assert_eq!(once.upcoming(Local::now()).count(), 0, "Disabled");
+    }
+
+    fn downtime_task(misfire: MisfirePolicy, now: DateTime<Local>) -> Task {
+        // Last ran 3.5 hours ago: due at -2.5h, -1.5h and -0.5h
+        let mut task = Task::new("Downtime", Interval::Hourly);
+        task.enabled = true;
+        task.misfire = misfire;
+        task.last_run = Some(now - Duration::minutes(210));
+        task
+    }
+
+    #[test]
+    fn test_misfire_run_once() {
+        let now = Local::now();
+        let due = downtime_task(MisfirePolicy::RunOnce, now).due_runs(now, &[]);
+        assert_eq!(due.run, vec![now - Duration::minutes(30)]);
+        assert_eq!(
+            due.skipped,
+            vec![now - Duration::minutes(150), now - Duration::minutes(90)]
+        );
+    }
+
+    #[test]
+    fn test_misfire_run_all() {
+        let now = Local::now();
+        let due = downtime_task(MisfirePolicy::RunAll, now).due_runs(now, &[]);
+        assert_eq!(due.run.len(), 3);
+        assert!(due.skipped.is_empty());
+    }
+
+    #[test]
+    fn test_misfire_skip() {
+        let now = Local::now();
+        let mut task = downtime_task(MisfirePolicy::Skip, now);
+        let due = task.due_runs(now, &[]);
+        assert!(due.run.is_empty(), "Even the latest run is 30 minutes late");
+        assert_eq!(due.skipped.len(), 3);
+
+        task.mark_skipped(*due.skipped.last().unwrap());
+        assert!(!task.should_run(now));
+        assert_eq!(task.next_run(now), Some(now + Duration::minutes(30)));
+
+        // A run picked up by a normal check is not a misfire
+        let on_time = now + Duration::minutes(31);
+        assert_eq!(
+            task.due_runs(on_time, &[]).run,
+            vec![now + Duration::minutes(30)]
+        );
+        assert!(task.due_runs(on_time, &[]).skipped.is_empty());
+    }
+
+    #[test]
+    fn test_misfire_catch_up_is_bounded() {
+        let now = Local::now();
+        let mut task = Task::new("Busy", Interval::Every(std::time::Duration::from_secs(1)));
+        task.enabled = true;
+        task.misfire = MisfirePolicy::RunAll;
+        task.last_run = Some(now - Duration::days(30));
+        let due = task.due_runs(now, &[]);
+        assert_eq!(due.run.len(), MAX_CATCH_UP);
+        assert!(due.truncated);
+    }
+
+    #[test]
+    fn test_blackout_defers_due_task() {
+        let mut task = Task::new("Nightly", Interval::Hourly);
+        task.enabled = true;
+        task.timezone = Some(chrono_tz::Europe::Berlin);
+        task.last_run = Some(berlin(2024, 5, 10, 22, 30).with_timezone(&Local));
+        let global = parse_blackouts("weekends").unwrap();
+        task.blackouts = parse_blackouts("22:00-07:00").unwrap();
+
+        // Due at 23:30 Friday, held back through the night and the weekend
+        let now = berlin(2024, 5, 10, 23, 45).with_timezone(&Local);
+        let due = task.due_runs(now, &global);
+        assert!(due.run.is_empty() && due.skipped.is_empty());
+        let deferral = due.deferred.unwrap();
+        assert_eq!(
+            deferral.until,
+            berlin(2024, 5, 13, 7, 0).with_timezone(&Local)
+        );
+        assert!(deferral.per_task);
+        assert_eq!(deferral.to_string(), "task blackout 22:00-07:00");
+
+        let saturday = berlin(2024, 5, 11, 12, 0).with_timezone(&Local);
+        assert!(!task.due_runs(saturday, &global).deferred.unwrap().per_task);
+    }
+
+    #[test]
+    fn test_next_wakeup_skips_blackouts_and_blocked_tasks() {
+        let mut task = Task::new("Nightly", Interval::Hourly);
+        task.enabled = true;
+        task.timezone = Some(chrono_tz::Europe::Berlin);
+        task.last_run = Some(berlin(2024, 5, 10, 20, 30).with_timezone(&Local));
+        task.blackouts = parse_blackouts("22:00-07:00").unwrap();
+        let global = parse_blackouts("weekends").unwrap();
+
+        let evening = berlin(2024, 5, 10, 21, 0).with_timezone(&Local);
+        assert_eq!(
+            task.next_wakeup(evening, &[], &[]),
+            Some(berlin(2024, 5, 10, 21, 30).with_timezone(&Local))
+        );
+        // The 22:30 run waits for the night and then the weekend
+        let late = berlin(2024, 5, 10, 22, 15).with_timezone(&Local);
+        task.last_run = Some(berlin(2024, 5, 10, 21, 30).with_timezone(&Local));
+        assert_eq!(
+            task.next_wakeup(late, &global, &[]),
+            Some(berlin(2024, 5, 13, 7, 0).with_timezone(&Local))
+        );
+
+        // A due task waiting on an upstream task has no wakeup of its own
+        let mut load = Task::new(
+            "Load",
+            Interval::Every(std::time::Duration::from_secs(15 * 60)),
+        );
+        load.enabled = true;
+        load.depends_on = vec!["Nightly".into()];
+        load.last_run = Some(late - Duration::minutes(30));
+        assert_eq!(load.next_wakeup(late, &[], &[task.clone()]), None);
+        task.last_run = Some(late);
+        assert_eq!(load.next_wakeup(late, &[], &[task]), Some(late));
+    }
+
+    #[test]
+    fn test_skip_policy_runs_after_blackout() {
+        let mut task = Task::new("Morning", Interval::Hourly);
+        task.enabled = true;
+        task.misfire = MisfirePolicy::Skip;
+        task.timezone = Some(chrono_tz::Europe::Berlin);
+        task.last_run = Some(berlin(2024, 5, 7, 4, 30).with_timezone(&Local));
+        let global = parse_blackouts("22:00-07:00").unwrap();
+
+        // The 06:30 run was deferred, not missed, so it runs when the window ends
+        let now = berlin(2024, 5, 7, 7, 1).with_timezone(&Local);
+        let due = task.due_runs(now, &global);
+        assert_eq!(
+            due.run,
+            vec![berlin(2024, 5, 7, 6, 30).with_timezone(&Local)]
+        );
+        assert_eq!(
+            due.skipped,
+            vec![berlin(2024, 5, 7, 5, 30).with_timezone(&Local)]
+        );
+    }
+
+    #[test]
+    fn test_start_and_end_dates() {
+        let mut task = Task::new("Project", Interval::Hourly);
+        task.enabled = true;
+        task.timezone = Some(chrono_tz::Europe::Berlin);
+        task.start_date = NaiveDate::from_ymd_opt(2024, 6, 1);
+        task.end_date = NaiveDate::from_ymd_opt(2024, 6, 30);
+
+        let before = berlin(2024, 5, 20, 12, 0).with_timezone(&Local);
+        assert!(!task.should_run(before));
+        assert_eq!(
+            task.next_run(before),
+            Some(berlin(2024, 6, 1, 0, 0).with_timezone(&Local))
+        );
+
+        // The end date is inclusive
+        task.last_run = Some(berlin(2024, 6, 30, 22, 30).with_timezone(&Local));
+        let last_day = berlin(2024, 6, 30, 23, 45).with_timezone(&Local);
+        assert!(task.should_run(last_day));
+        assert!(!task.is_expired(last_day));
+
+        let after = berlin(2024, 7, 1, 0, 30).with_timezone(&Local);
+        task.last_run = Some(last_day);
+        assert!(!task.should_run(after));
+        assert_eq!(task.next_run(after), None);
+        assert!(task.is_expired(after));
+    }
+
+    #[test]
+    fn test_max_runs() {
+        let now = Local::now();
+        let mut task = Task::new("Limited", Interval::Hourly);
+        task.enabled = true;
+        task.misfire = MisfirePolicy::RunAll;
+        task.max_runs = Some(3);
+        task.run_count = 1;
+        task.last_run = Some(now - Duration::minutes(330));
+
+        // Five runs were missed but only two are left
+        assert_eq!(task.due_runs(now, &[]).run.len(), 2);
+        assert_eq!(task.upcoming(now).count(), 2);
+
+        task.mark_complete(now);
+        task.mark_complete(now);
+        assert!(task.is_expired(now));
+        assert!(!task.should_run(now + Duration::hours(2)));
+    }
+
+    fn bank_calendar() -> BusinessCalendar {
+        let mut calendar = BusinessCalendar::new("Bank");
+        calendar
+            .holidays
+            .insert(NaiveDate::from_ymd_opt(2024, 12, 25).unwrap());
+        calendar
+            .holidays
+            .insert(NaiveDate::from_ymd_opt(2024, 12, 26).unwrap());
+        calendar
+    }
+
+    #[test]
+    fn test_holiday_moves_to_next_business_day() {
+        let mut tasks = vec![Task::new(
+            "Payroll",
+            Interval::Monthly {
+                day: MonthDay::Day(25),
+                time: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            },
+        )];
+        tasks[0].enabled = true;
+        tasks[0].timezone = Some(chrono_tz::Europe::Berlin);
+        tasks[0].calendar = Some("Bank".into());
+        tasks[0].holiday_policy = HolidayPolicy::NextBusinessDay;
+        tasks[0].last_run = Some(berlin(2024, 11, 25, 9, 0).with_timezone(&Local));
+        attach_calendars(&mut tasks, &[bank_calendar()]).unwrap();
+
+        // Christmas and Boxing Day are holidays, so December's run is on Friday 27th
+        let now = berlin(2024, 12, 1, 0, 0).with_timezone(&Local);
+        let runs: Vec<_> = tasks[0].upcoming(now).take(3).collect();
+        assert_eq!(
+            runs,
+            vec![
+                berlin(2024, 12, 27, 9, 0).with_timezone(&Local),
+                // January 25th 2025 is a Saturday
+                berlin(2025, 1, 27, 9, 0).with_timezone(&Local),
+                berlin(2025, 2, 25, 9, 0).with_timezone(&Local),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_holiday_skips_run() {
+        let mut tasks = vec![Task::new(
+            "Report",
+            Interval::At {
+                time: NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+                weekdays: vec![],
+            },
+        )];
+        tasks[0].enabled = true;
+        tasks[0].timezone = Some(chrono_tz::Europe::Berlin);
+        tasks[0].calendar = Some("Bank".into());
+        tasks[0].last_run = Some(berlin(2024, 12, 24, 8, 0).with_timezone(&Local));
+        attach_calendars(&mut tasks, &[bank_calendar()]).unwrap();
+
+        let now = berlin(2024, 12, 24, 12, 0).with_timezone(&Local);
+        assert_eq!(
+            tasks[0].next_run(now),
+            Some(berlin(2024, 12, 27, 8, 0).with_timezone(&Local))
+        );
+
+        tasks[0].calendar = Some("Missing".into());
+        assert!(attach_calendars(&mut tasks, &[bank_calendar()]).is_err());
+    }
+
+    #[test]
+    fn test_dependency_blocks_until_upstream_completes() {
+        let now = Local::now();
+        let mut extract = Task::new("Extract", Interval::Daily);
+        extract.last_run = Some(now - Duration::days(1));
+        let mut load = Task::new("Load", Interval::Daily);
+        load.depends_on = vec!["Extract".into()];
+        load.last_run = Some(now - Duration::hours(23));
+
+        // Extract last completed before Load's previous run, so this cycle waits
+        let tasks = vec![extract.clone(), load.clone()];
+        assert_eq!(load.blocked_by(&tasks), vec!["Extract".to_string()]);
+
+        extract.last_run = Some(now);
+        assert!(load.blocked_by(&[extract, load.clone()]).is_empty());
+        assert_eq!(load.blocked_by(&[]), vec!["Extract".to_string()]);
+    }
+
+    #[test]
+    fn test_dependency_waits_for_upstream_action_to_succeed() {
+        let now = Local::now();
+        let finished = |status| RunRecord {
+            started: now,
+            finished: now + Duration::seconds(5),
+            status,
+            stdout: None,
+            stderr: None,
+            output_error: None,
+        };
+        let mut extract = Task::new("Extract", Interval::Daily);
+        extract.action = Action::from_command_line("extract.sh").unwrap();
+        let mut load = Task::new("Load", Interval::Daily);
+        load.depends_on = vec!["Extract".into()];
+        load.last_run = Some(now - Duration::hours(23));
+
+        // Started but still running
+        extract.mark_complete(now);
+        assert_eq!(
+            load.blocked_by(&[extract.clone()]),
+            vec!["Extract".to_string()]
+        );
+
+        extract.record_outcome(finished(RunStatus::Exited(1)));
+        assert_eq!(
+            load.blocked_by(&[extract.clone()]),
+            vec!["Extract".to_string()]
+        );
+
+        extract.record_outcome(finished(RunStatus::Exited(0)));
+        assert!(load.blocked_by(&[extract]).is_empty());
+    }
+
+    #[test]
+    fn test_dependency_cycle_detection() {
+        let mut a = Task::new("A", Interval::Daily);
+        let mut b = Task::new("B", Interval::Daily);
+        let mut c = Task::new("C", Interval::Daily);
+        b.depends_on = vec!["A".into()];
+        c.depends_on = vec!["B".into(), "A".into()];
+        assert!(validate_dependencies(&[a.clone(), b.clone(), c.clone()]).is_ok());
+
+        a.depends_on = vec!["C".into()];
+        let err = validate_dependencies(&[a.clone(), b.clone(), c.clone()]).unwrap_err();
+        assert_eq!(err.to_string(), "dependency cycle: A -> C -> B -> A");
+
+        a.depends_on = vec!["Nope".into()];
+        assert!(validate_dependencies(&[a, b, c]).is_err());
+    }
+
+    #[test]
+    fn test_tag_enable_disable() {
+        let mut tasks = vec![
+            Task::new("Backup", Interval::Daily),
+            Task::new("Report", Interval::Daily),
+            Task::new("Cleanup", Interval::Weekly),
+        ];
+        tasks[0].tags = ["ops".to_string(), "nightly".to_string()].into();
+        tasks[1].tags = ["finance".to_string()].into();
+        tasks[2].tags = ["ops".to_string()].into();
+        tasks[2].enabled = true;
+
+        assert_eq!(
+            all_tags(&tasks).into_iter().collect::<Vec<_>>(),
+            vec!["finance", "nightly", "ops"]
+        );
+        assert_eq!(set_tag_enabled(&mut tasks, "ops", true), 1);
+        assert!(tasks[0].enabled && tasks[2].enabled && !tasks[1].enabled);
+        assert_eq!(set_tag_enabled(&mut tasks, "ops", false), 2);
+        assert!(!tasks[0].enabled && !tasks[2].enabled);
+    }
+
+    #[test]
+    fn test_snooze_pauses_then_resumes() {
+        let now = Local::now();
+        let mut task = Task::new("Sync", Interval::Hourly);
+        task.enabled = true;
+        task.misfire = MisfirePolicy::Skip;
+        task.last_run = Some(now - Duration::minutes(90));
+        task.paused_until = Some(now + Duration::hours(1));
+
+        assert!(task.is_paused(now));
+        assert!(!task.should_run(now));
+        assert!(task.due_runs(now, &[]).is_empty());
+        assert_eq!(task.next_run(now), task.paused_until);
+
+        // The run held back by the snooze starts on time when it ends
+        let resumed = now + Duration::minutes(61);
+        assert!(!task.is_paused(resumed));
+        let due = task.due_runs(resumed, &[]);
+        assert_eq!(due.run, vec![now + Duration::minutes(30)]);
+        assert_eq!(due.skipped, vec![now - Duration::minutes(30)]);
+    }
+
+    #[test]
+    fn test_start_delays_jitter() {
+        use rand::SeedableRng;
+
+        let mut jittery = Task::new("Jittery", Interval::Hourly);
+        jittery.jitter = Some(std::time::Duration::from_secs(30));
+        let plain = Task::new("Plain", Interval::Hourly);
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let delays = start_delays(&[&jittery, &plain], None, &mut rng);
+        assert!(delays[0] <= std::time::Duration::from_secs(30));
+        assert_eq!(delays[1], std::time::Duration::ZERO);
+
+        // The same seed gives the same delays
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        assert_eq!(start_delays(&[&jittery, &plain], None, &mut rng), delays);
+    }
+
+    #[test]
+    fn test_start_delays_spread() {
+        use rand::SeedableRng;
+
+        let tasks: Vec<Task> = (0..4)
+            .map(|i| Task::new(format!("T{}", i), Interval::Hourly))
+            .collect();
+        let refs: Vec<&Task> = tasks.iter().collect();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let mut delays = start_delays(&refs, Some(std::time::Duration::from_secs(60)), &mut rng);
+        delays.sort();
+        assert_eq!(
+            delays,
+            [0, 15, 30, 45].map(std::time::Duration::from_secs).to_vec()
+        );
+
+        // A single due task is never delayed by spreading
+        let delays = start_delays(
+            &refs[..1],
+            Some(std::time::Duration::from_secs(60)),
+            &mut rng,
+        );
+        assert_eq!(delays, vec![std::time::Duration::ZERO]);
+    }
 }
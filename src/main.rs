@@ -1,6 +1,7 @@
 // ---------- Imports ----------
-use anyhow::{anyhow, Context};
-use chrono::{DateTime, Local};
+use anyhow::anyhow;
+use chrono::{Datelike, Local, NaiveDate, NaiveTime, Weekday};
+use chrono_tz::Tz;
 use directories::ProjectDirs;
 use iced::keyboard;
 use iced::theme::Theme;
@@ -11,11 +12,20 @@ use iced::widget::{
 use iced::Command;
 use iced::{executor, time, Element, Length, Subscription};
 use iced::{Alignment, Application};
-use serde::{Deserialize, Serialize};
+use iced_task_scheduler::{
+    all_tags, attach_calendars, format_blackouts, load_holidays, load_settings, load_tasks,
+    parse_blackouts, parse_env, parse_phrase, project_file_path, prune_run_output, replace_tasks,
+    run_output_dir, save_settings, set_tag_enabled, simulate, start_delays, validate_dependencies,
+    validate_new_task, Action, BusinessCalendar, Clock, DueRuns, HolidayPolicy, Interval,
+    MisfirePolicy, MonthDay, OutputCapture, Priority, RunQueue, RunRecord, RunStatus, Scheduler,
+    Settings, Simulation, SystemClock, Task,
+};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tracing::{error, info};
+use tracing::{debug, error, info};
 use tracing_appender::{non_blocking, rolling};
 
 // ---------- Messages ----------
@@ -24,9 +34,61 @@ enum Message {
     SwitchTo(Screen),
     ToggleSort,
     ToastTick,
-    TaskCheckComplete(Vec<(usize, bool)>),
+    // A due run whose start was delayed by jitter or spreading, keyed by task name
+    RunDelayed(String, DueRuns),
+    // A started run has finished and frees its slot, keyed by task name, with the
+    // result of its action if it has one
+    RunFinished(String, Option<RunRecord>),
     TaskNameChanged(String),
-    IntervalChanged(Interval),
+    IntervalChanged(IntervalKind),
+    CronExprChanged(String),
+    PhraseChanged(String),
+    EveryAmountChanged(String),
+    EveryUnitChanged(PeriodUnit),
+    AtTimeChanged(String),
+    ToggleAtWeekday(Weekday),
+    MonthDayChanged(String),
+    ToggleMonthlyLastWeekday(bool),
+    MonthlyWeekdayChanged(Weekday),
+    YearlyMonthChanged(MonthChoice),
+    OnceDateChanged(String),
+    TimezoneChanged(String),
+    MisfireChanged(MisfirePolicy),
+    JitterChanged(String),
+    ToggleSpread(bool),
+    BlackoutsChanged(String),
+    StartDateChanged(String),
+    EndDateChanged(String),
+    MaxRunsChanged(String),
+    DependsOnChanged(String),
+    CommandChanged(String),
+    WorkingDirChanged(String),
+    TimeoutChanged(String),
+    EnvChanged(String),
+    Snooze(usize, SnoozePreset),
+    TagsChanged(String),
+    TagFilterChanged(TagFilter),
+    SetTagEnabled(String, bool),
+    DryRunHorizonChanged(DryRunHorizon),
+    RunDryRun,
+    DryRunComplete(chrono::DateTime<Local>, Simulation),
+    ShowRunOutput(PathBuf),
+    RunOutputLoaded(PathBuf, Result<String, String>),
+    CloseRunOutput,
+    PriorityChanged(Priority),
+    MaxConcurrentChanged(String),
+    ApplyMaxConcurrent,
+    CalendarChoiceChanged(CalendarChoice),
+    HolidayPolicyChanged(HolidayPolicy),
+    CalendarNameChanged(String),
+    CalendarFileChanged(String),
+    ToggleCalendarWeekdaysOnly(bool),
+    AddCalendar,
+    CalendarLoaded(Result<BusinessCalendar, String>),
+    DeleteCalendar(usize),
+    GlobalBlackoutsChanged(String),
+    ApplyGlobalBlackouts,
+    SettingsLoaded(Result<Settings, String>),
     AddTask,
     DeletePressed(usize),
     ConfirmDelete,
@@ -51,54 +113,330 @@ enum Screen {
     Overview,
     NewTask,
     History,
+    Settings,
+    DryRun,
 }
 
-// ---------- Interval ----------
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-enum Interval {
+// ---------- Interval Editor ----------
+// The pick_list needs a plain, copyable choice; the parameters for the richer
+// interval kinds are edited in separate inputs on the New Task screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IntervalKind {
+    Describe,
     Hourly,
     Daily,
     Weekly,
+    Every,
+    At,
+    Monthly,
+    Yearly,
+    Once,
+    Cron,
+}
+
+impl IntervalKind {
+    const ALL: [IntervalKind; 10] = [
+        IntervalKind::Describe,
+        IntervalKind::Hourly,
+        IntervalKind::Daily,
+        IntervalKind::Weekly,
+        IntervalKind::Every,
+        IntervalKind::At,
+        IntervalKind::Monthly,
+        IntervalKind::Yearly,
+        IntervalKind::Once,
+        IntervalKind::Cron,
+    ];
+}
+
+impl std::fmt::Display for IntervalKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IntervalKind::Describe => write!(f, "Describe in words"),
+            IntervalKind::Hourly => write!(f, "Hourly"),
+            IntervalKind::Daily => write!(f, "Daily"),
+            IntervalKind::Weekly => write!(f, "Weekly"),
+            IntervalKind::Every => write!(f, "Every..."),
+            IntervalKind::At => write!(f, "At time of day"),
+            IntervalKind::Monthly => write!(f, "Monthly"),
+            IntervalKind::Yearly => write!(f, "Yearly"),
+            IntervalKind::Once => write!(f, "Once"),
+            IntervalKind::Cron => write!(f, "Cron"),
+        }
+    }
+}
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+// Month number (1-12) shown by name in the Yearly pick_list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct MonthChoice(u32);
+
+impl MonthChoice {
+    const ALL: [MonthChoice; 12] = [
+        MonthChoice(1),
+        MonthChoice(2),
+        MonthChoice(3),
+        MonthChoice(4),
+        MonthChoice(5),
+        MonthChoice(6),
+        MonthChoice(7),
+        MonthChoice(8),
+        MonthChoice(9),
+        MonthChoice(10),
+        MonthChoice(11),
+        MonthChoice(12),
+    ];
+}
+
+impl std::fmt::Display for MonthChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match chrono::Month::try_from(self.0 as u8) {
+            Ok(month) => write!(f, "{}", month.name()),
+            Err(_) => write!(f, "?"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PeriodUnit {
+    Seconds,
+    Minutes,
+    Hours,
+    Days,
+    Weeks,
 }
 
-impl std::fmt::Display for Interval {
+impl PeriodUnit {
+    const ALL: [PeriodUnit; 5] = [
+        PeriodUnit::Seconds,
+        PeriodUnit::Minutes,
+        PeriodUnit::Hours,
+        PeriodUnit::Days,
+        PeriodUnit::Weeks,
+    ];
+
+    fn seconds(self) -> u64 {
+        match self {
+            PeriodUnit::Seconds => 1,
+            PeriodUnit::Minutes => 60,
+            PeriodUnit::Hours => 60 * 60,
+            PeriodUnit::Days => 24 * 60 * 60,
+            PeriodUnit::Weeks => 7 * 24 * 60 * 60,
+        }
+    }
+}
+
+impl std::fmt::Display for PeriodUnit {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Interval::Hourly => write!(f, "Hourly"),
-            Interval::Daily => write!(f, "Daily"),
-            Interval::Weekly => write!(f, "Weekly"),
+            PeriodUnit::Seconds => write!(f, "seconds"),
+            PeriodUnit::Minutes => write!(f, "minutes"),
+            PeriodUnit::Hours => write!(f, "hours"),
+            PeriodUnit::Days => write!(f, "days"),
+            PeriodUnit::Weeks => write!(f, "weeks"),
         }
     }
 }
 
-// ---------- Task ----------
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct Task {
-    name: String,
-    interval: Interval,
-    last_run: Option<DateTime<Local>>,
-    enabled: bool,
+// A task's business calendar, by name; `None` runs on any day
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CalendarChoice(Option<String>);
+
+impl std::fmt::Display for CalendarChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.0 {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "Any day"),
+        }
+    }
 }
 
-impl Task {
-    fn new(name: impl Into<String>, interval: Interval) -> Self {
-        Self {
-            name: name.into(),
-            interval,
-            last_run: None,
-            enabled: false, // Tasks are disabled by default
+// The overview's tag filter; `None` shows every task
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TagFilter(Option<String>);
+
+impl std::fmt::Display for TagFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.0 {
+            Some(tag) => write!(f, "#{}", tag),
+            None => write!(f, "All tags"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SnoozePreset {
+    OneHour,
+    Tomorrow,
+    NextWeek,
+    Resume,
+}
+
+impl SnoozePreset {
+    const ALL: [SnoozePreset; 4] = [
+        SnoozePreset::OneHour,
+        SnoozePreset::Tomorrow,
+        SnoozePreset::NextWeek,
+        SnoozePreset::Resume,
+    ];
+
+    // When the snooze ends; `None` clears it. Tomorrow and next week start at
+    // midnight, next week on Monday.
+    fn until(self, now: chrono::DateTime<Local>) -> Option<chrono::DateTime<Local>> {
+        let start_of = |date: NaiveDate| {
+            date.and_time(NaiveTime::MIN)
+                .and_local_timezone(Local)
+                .earliest()
+        };
+        let today = now.date_naive();
+        match self {
+            SnoozePreset::OneHour => Some(now + chrono::Duration::hours(1)),
+            SnoozePreset::Tomorrow => start_of(today.succ_opt()?),
+            SnoozePreset::NextWeek => start_of(
+                today + chrono::Duration::days(7 - today.weekday().num_days_from_monday() as i64),
+            ),
+            SnoozePreset::Resume => None,
         }
     }
 }
 
+impl std::fmt::Display for SnoozePreset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnoozePreset::OneHour => write!(f, "For 1 hour"),
+            SnoozePreset::Tomorrow => write!(f, "Until tomorrow"),
+            SnoozePreset::NextWeek => write!(f, "Until next week"),
+            SnoozePreset::Resume => write!(f, "Resume now"),
+        }
+    }
+}
+
+// How far ahead the Dry Run screen simulates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DryRunHorizon {
+    Day,
+    Week,
+    Month,
+}
+
+impl DryRunHorizon {
+    const ALL: [DryRunHorizon; 3] = [
+        DryRunHorizon::Day,
+        DryRunHorizon::Week,
+        DryRunHorizon::Month,
+    ];
+
+    fn duration(self) -> chrono::Duration {
+        match self {
+            DryRunHorizon::Day => chrono::Duration::days(1),
+            DryRunHorizon::Week => chrono::Duration::days(7),
+            DryRunHorizon::Month => chrono::Duration::days(30),
+        }
+    }
+}
+
+impl std::fmt::Display for DryRunHorizon {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DryRunHorizon::Day => write!(f, "Next 24 hours"),
+            DryRunHorizon::Week => write!(f, "Next 7 days"),
+            DryRunHorizon::Month => write!(f, "Next 30 days"),
+        }
+    }
+}
+
+// The dry run list stops here; the summary still counts every run
+const MAX_DRY_RUN_ROWS: usize = 500;
+
 // ---------- Toast ----------
 // ---------- App ----------
 struct TaskScheduler {
+    // Every scheduling decision reads the time from here
+    clock: Arc<dyn Clock>,
     screen: Screen,
     tasks: Vec<Task>,
+    // Position of each task in `tasks` by name; kept in step with `tasks`
+    task_index: HashMap<String, usize>,
     task_name: String,
-    interval: Interval,
+    interval_kind: IntervalKind,
+    cron_expr: String,
+    // Plain-language schedule such as "weekdays at 9am"
+    phrase: String,
+    every_amount: String,
+    every_unit: PeriodUnit,
+    at_time: String,
+    // Empty means every day
+    at_weekdays: Vec<Weekday>,
+    // Day of month for Monthly and Yearly
+    month_day: String,
+    monthly_last_weekday: bool,
+    monthly_weekday: Weekday,
+    yearly_month: MonthChoice,
+    once_date: String,
+    // IANA zone name; blank means the system's local zone
+    timezone: String,
+    misfire: MisfirePolicy,
+    jitter_secs: String,
+    // Blackout windows in the "22:00-07:00, weekends" text form
+    blackouts: String,
+    // Validity window; blank fields mean no limit
+    start_date: String,
+    end_date: String,
+    max_runs: String,
+    calendar: CalendarChoice,
+    holiday_policy: HolidayPolicy,
+    // Comma-separated names of the tasks this one runs after
+    depends_on: String,
+    // The new task's action: a command line, its directory and KEY=value pairs
+    command_line: String,
+    working_dir: String,
+    env_vars: String,
+    // Longest the command may run, in seconds; blank means no limit
+    timeout_secs: String,
+    priority: Priority,
+    // Comma-separated tags for the new task
+    tags: String,
+    tag_filter: TagFilter,
+    dry_run_horizon: DryRunHorizon,
+    // The last simulation and when it started
+    dry_run: Option<(chrono::DateTime<Local>, Simulation)>,
+    settings: Settings,
+    global_blackouts: String,
+    // New calendar form on the Settings screen
+    calendar_name: String,
+    calendar_file: String,
+    calendar_weekdays_only: bool,
+    // Blank means unlimited
+    max_concurrent: String,
+    // Due runs waiting for a slot under the concurrency limit, plus those running
+    run_queue: RunQueue,
+    // Names of tasks waiting out a start delay, so later checks don't queue them twice
+    delayed_runs: HashSet<String>,
+    // Catch-up occurrences still to run after a task's current run. Each is queued
+    // as its own run when the one before it finishes, so the concurrency limit
+    // and priorities apply between them.
+    catch_up_runs: HashMap<String, Vec<chrono::DateTime<Local>>>,
+    // When the scheduler last checked the tasks
+    last_check: Option<chrono::DateTime<Local>>,
+    // The tasks indexed by when they next fall due; kept in step with `tasks`
+    scheduler: Scheduler,
     history: Vec<String>,
+    // Lines dropped from the front of `history` to keep it short
+    history_dropped: usize,
+    // Saved stdout and stderr of action runs, keyed by the history line of the run,
+    // counting dropped lines
+    run_outputs: HashMap<usize, (Option<PathBuf>, Option<PathBuf>)>,
+    // The output file opened from the History screen and its contents
+    output_view: Option<(PathBuf, Result<String, String>)>,
     is_saving: bool,
     is_loading: bool,
     pending_delete: Option<usize>,
@@ -112,122 +450,127 @@ struct TaskScheduler {
     is_dark: bool,
 }
 
-// ---------- Helper Functions ----------
-async fn check_tasks(tasks: Vec<Task>) -> Vec<(usize, bool)> {
-    let now = Local::now();
-    let mut results = Vec::new();
+// Due tasks are spread across this window when spreading is on
+const SPREAD_WINDOW: Duration = Duration::from_secs(60);
 
-    for (idx, task) in tasks.iter().enumerate() {
-        if !task.enabled {
-            continue;
-        }
+// The History screen keeps this many of the latest lines
+const MAX_HISTORY_LINES: usize = 1000;
 
-        let should_run = if let Some(last) = task.last_run {
-            match task.interval {
-                Interval::Hourly => now.signed_duration_since(last).num_hours() >= 1,
-                Interval::Daily => now.signed_duration_since(last).num_days() >= 1,
-                Interval::Weekly => now.signed_duration_since(last).num_weeks() >= 1,
-            }
-        } else {
-            true // Never run before
-        };
+// Each run keeps this much of its stdout and of its stderr
+const MAX_RUN_OUTPUT_BYTES: u64 = 1024 * 1024;
 
-        if should_run {
-            results.push((idx, true));
-        }
-    }
+// The oldest saved run output is deleted once the folder grows past this
+const MAX_RUN_OUTPUT_TOTAL_BYTES: u64 = 100 * 1024 * 1024;
 
-    results
-}
+// The wakeup timer re-reads the clock at least this often, so a timer that fell
+// behind (e.g. while the computer slept) still fires close to the due time
+const MAX_TIMER_SLEEP: Duration = Duration::from_secs(300);
 
-async fn save_tasks_cmd(tasks: Vec<Task>) -> Result<(), anyhow::Error> {
-    let path = project_file_path().ok_or_else(|| anyhow!("no config dir"))?;
-    let json = serde_json::to_string_pretty(&tasks).context("serializing tasks")?;
-    // Write atomically: write to temp file and then rename
-    let mut tmp = path.clone();
-    tmp.set_extension("json.tmp");
-    fs::write(&tmp, &json).with_context(|| format!("writing temp file {}", tmp.display()))?;
-    fs::rename(&tmp, &path)
-        .with_context(|| format!("renaming {} -> {}", tmp.display(), path.display()))?;
-    Ok(())
+// ---------- Helper Functions ----------
+// The last run's result in green or red, so failures stand out in the task list
+fn view_outcome(task: &Task) -> iced::widget::Text<'static> {
+    let Some(run) = &task.last_outcome else {
+        return text(if task.action.is_some() {
+            "Not run yet"
+        } else {
+            "-"
+        })
+        .style(iced::theme::Text::Color([0.5, 0.5, 0.5].into()));
+    };
+    let secs = run.duration().num_seconds();
+    let (label, color) = match &run.status {
+        status if status.is_success() => (format!("Succeeded in {}s", secs), [0.0, 0.6, 0.0]),
+        RunStatus::Failed(_) => ("Could not start".to_string(), [0.8, 0.0, 0.0]),
+        RunStatus::TimedOut(_) => (format!("Timed out after {}s", secs), [0.8, 0.0, 0.0]),
+        status => (format!("Failed ({})", status), [0.8, 0.0, 0.0]),
+    };
+    text(label).style(iced::theme::Text::Color(color.into()))
 }
 
-async fn load_tasks_cmd() -> Result<Vec<Task>, anyhow::Error> {
-    let path = project_file_path().ok_or_else(|| anyhow!("no config dir"))?;
-    if !path.exists() {
-        return Ok(vec![]);
-    }
-    let data = fs::read_to_string(&path)
-        .with_context(|| format!("reading tasks file {}", path.display()))?;
-    // Try direct deserialization first (normal case)
-    if let Ok(list) = serde_json::from_str::<Vec<Task>>(&data) {
-        return Ok(list);
-    }
-
-    // Fallback: be tolerant of older/variant formats. Try parsing manually.
-    let v: serde_json::Value = serde_json::from_str(&data).context("parsing tasks JSON")?;
-    let arr = v
-        .as_array()
-        .ok_or_else(|| anyhow!("expected an array of tasks"))?;
-    let mut out = Vec::with_capacity(arr.len());
-    for item in arr {
-        let name = item
-            .get("name")
-            .and_then(|s| s.as_str())
-            .unwrap_or("")
-            .to_string();
-
-        let interval = match item.get("interval").and_then(|s| s.as_str()) {
-            Some("Hourly") | Some("hourly") => Interval::Hourly,
-            Some("Weekly") | Some("weekly") => Interval::Weekly,
-            _ => Interval::Daily,
-        };
+fn parse_optional_date(input: &str) -> Result<Option<NaiveDate>, anyhow::Error> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(None);
+    }
+    NaiveDate::parse_from_str(input, "%Y-%m-%d")
+        .map(Some)
+        .map_err(|_| anyhow!("'{}' is not a date in YYYY-MM-DD format", input))
+}
 
-        let enabled = item
-            .get("enabled")
-            .and_then(|b| b.as_bool())
-            .unwrap_or(false);
-
-        let last_run = item.get("last_run").and_then(|lr| {
-            if lr.is_null() {
-                None
-            } else if let Some(s) = lr.as_str() {
-                // Try parsing as RFC3339 first
-                if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
-                    Some(dt.with_timezone(&Local))
-                } else {
-                    None
-                }
-            } else {
-                None
+// ---------- View Implementations ----------
+impl TaskScheduler {
+    fn next_run_label(&self, task: &Task, now: chrono::DateTime<Local>) -> String {
+        if task.is_completed() || task.is_expired(now) {
+            return "—".into();
+        }
+        if self.run_queue.running().contains(&task.name.as_str()) {
+            return "Running".into();
+        }
+        if let Some(pos) = self
+            .run_queue
+            .queued()
+            .iter()
+            .position(|q| q.task == task.name)
+        {
+            return format!("Queued (#{})", pos + 1);
+        }
+        if let Some(until) = task
+            .paused_until
+            .filter(|_| task.enabled && task.is_paused(now))
+        {
+            return format!("Snoozed until {}", until.format("%Y-%m-%d %H:%M"));
+        }
+        let blocked_by = task.blocked_by_in(|name| Some(&self.tasks[self.task_position(name)?]));
+        match task.next_run(now) {
+            None if !task.enabled => "Disabled".into(),
+            None => "Never".into(),
+            Some(next) if next <= now && !blocked_by.is_empty() => {
+                format!("Blocked by {}", blocked_by.join(", "))
             }
-        });
-
-        out.push(Task {
-            name,
-            interval,
-            last_run,
-            enabled,
-        });
+            Some(next) => match task.deferral(&self.settings.blackouts, next) {
+                Some(deferral) => format!(
+                    "Deferred until {} ({})",
+                    deferral.until.format("%Y-%m-%d %H:%M"),
+                    deferral
+                ),
+                None if next <= now => "Due now".into(),
+                None => next.format("%Y-%m-%d %H:%M:%S").to_string(),
+            },
+        }
     }
 
-    Ok(out)
-}
-
-fn project_file_path() -> Option<PathBuf> {
-    if let Some(dirs) = ProjectDirs::from("com", "example", "task_scheduler_gui") {
-        let dir = dirs.config_dir();
-        let _ = fs::create_dir_all(dir);
-        let mut path = dir.to_path_buf();
-        path.push("tasks.json");
-        Some(path)
-    } else {
-        None
+    fn view_run_queue(&self) -> Element<'_, Message> {
+        let limit = match self.settings.max_concurrent {
+            Some(max) => format!("limit {}", max),
+            None => "no limit".into(),
+        };
+        let running = self.run_queue.running();
+        let mut lines: Vec<Element<Message>> = vec![text(format!(
+            "Run queue: {} running, {} waiting ({})",
+            running.len(),
+            self.run_queue.queued().len(),
+            limit
+        ))
+        .into()];
+        if !running.is_empty() {
+            lines.push(text(format!("Running: {}", running.join(", "))).into());
+        }
+        for (pos, run) in self.run_queue.queued().iter().enumerate() {
+            lines.push(
+                text(format!(
+                    "{}. {} ({} priority, waiting since {})",
+                    pos + 1,
+                    run.task,
+                    run.priority,
+                    run.queued_at.format("%H:%M:%S")
+                ))
+                .size(14)
+                .into(),
+            );
+        }
+        container(column(lines).spacing(4)).padding(10).into()
     }
-}
 
-// ---------- View Implementations ----------
-impl TaskScheduler {
     fn view_overview(&self) -> Element<'_, Message> {
         // Responsive header: title takes larger portion, actions are grouped on the right
         let header_actions = row![
@@ -237,10 +580,35 @@ impl TaskScheduler {
                 "Sort ↑"
             })
             .on_press(Message::ToggleSort),
+            pick_list(
+                std::iter::once(TagFilter(None))
+                    .chain(
+                        all_tags(&self.tasks)
+                            .into_iter()
+                            .map(|t| TagFilter(Some(t)))
+                    )
+                    .collect::<Vec<_>>(),
+                Some(self.tag_filter.clone()),
+                Message::TagFilterChanged
+            ),
             button("+ New Task").on_press(Message::SwitchTo(Screen::NewTask)),
         ]
         .spacing(8);
 
+        // Group actions for the filtered tag
+        let tag_actions: Element<Message> = match &self.tag_filter.0 {
+            Some(tag) => row![
+                text(format!("Tasks tagged #{}:", tag)),
+                button("Enable all").on_press(Message::SetTagEnabled(tag.clone(), true)),
+                button("Disable all").on_press(Message::SetTagEnabled(tag.clone(), false)),
+            ]
+            .spacing(8)
+            .padding([0, 10])
+            .align_items(Alignment::Center)
+            .into(),
+            None => column![].into(),
+        };
+
         let page_header = row![
             container(text("Tasks").size(32)).width(Length::FillPortion(3)),
             horizontal_space().width(Length::FillPortion(1)),
@@ -256,14 +624,21 @@ impl TaskScheduler {
             text("Task Name").width(Length::FillPortion(4)),
             text("Interval").width(Length::FillPortion(1)),
             text("Last Run").width(Length::FillPortion(2)),
-            text("Actions").width(Length::FillPortion(1))
+            text("Result").width(Length::FillPortion(2)),
+            text("Next Run").width(Length::FillPortion(2)),
+            text("Actions").width(Length::FillPortion(2))
         ]
         .spacing(10)
         .padding(10)
         .align_items(Alignment::Center);
 
         // Create a sorted copy of task indices
-        let mut task_indices: Vec<usize> = (0..self.tasks.len()).collect();
+        let mut task_indices: Vec<usize> = (0..self.tasks.len())
+            .filter(|&idx| match &self.tag_filter.0 {
+                Some(tag) => self.tasks[idx].tags.contains(tag),
+                None => true,
+            })
+            .collect();
         task_indices.sort_by(|&a, &b| {
             let ordering = self.tasks[a].name.cmp(&self.tasks[b].name);
             if self.sort_asc {
@@ -273,26 +648,57 @@ impl TaskScheduler {
             }
         });
 
+        let now = self.clock.now();
         let tasks: Vec<Element<Message>> = task_indices
             .iter()
             .map(|&idx| {
                 let task = &self.tasks[idx];
+                // Finished one-shot tasks can't run again, so there is nothing to toggle
+                let status: Element<Message> = if task.is_completed() {
+                    text("Completed").width(Length::FillPortion(1)).into()
+                } else if task.is_expired(now) {
+                    text("Expired").width(Length::FillPortion(1)).into()
+                } else {
+                    checkbox("", task.enabled)
+                        .width(Length::FillPortion(1))
+                        .on_toggle(move |_| Message::ToggleEnable(idx))
+                        .into()
+                };
                 container(
                     row![
-                        checkbox("", task.enabled)
-                            .width(Length::FillPortion(1))
-                            .on_toggle(move |_| Message::ToggleEnable(idx)),
-                        text(&task.name).width(Length::FillPortion(4)),
-                        text(task.interval.to_string()).width(Length::FillPortion(1)),
+                        status,
+                        text(if task.tags.is_empty() {
+                            task.name.clone()
+                        } else {
+                            let tags: Vec<String> =
+                                task.tags.iter().map(|t| format!("#{}", t)).collect();
+                            format!("{}  {}", task.name, tags.join(" "))
+                        })
+                        .width(Length::FillPortion(4)),
+                        text(match task.timezone {
+                            Some(tz) => format!("{} ({})", task.interval, tz.name()),
+                            None => task.interval.to_string(),
+                        })
+                        .width(Length::FillPortion(1)),
                         text(if let Some(last) = task.last_run {
                             last.format("%Y-%m-%d %H:%M:%S").to_string()
                         } else {
                             "Never".into()
                         })
                         .width(Length::FillPortion(2)),
-                        button("Delete")
-                            .width(Length::FillPortion(1))
-                            .on_press(Message::DeletePressed(idx))
+                        view_outcome(task).width(Length::FillPortion(2)),
+                        text(self.next_run_label(task, now)).width(Length::FillPortion(2)),
+                        row![
+                            pick_list(
+                                &SnoozePreset::ALL[..],
+                                None::<SnoozePreset>,
+                                move |preset| { Message::Snooze(idx, preset) }
+                            )
+                            .placeholder("Snooze"),
+                            button("Delete").on_press(Message::DeletePressed(idx)),
+                        ]
+                        .spacing(4)
+                        .width(Length::FillPortion(2))
                     ]
                     .spacing(10)
                     .padding(10)
@@ -321,9 +727,15 @@ impl TaskScheduler {
                 .into()
         };
 
-        let content = column![page_header, headers, tasks_container]
-            .spacing(0)
-            .width(Length::Fill);
+        let content = column![
+            page_header,
+            tag_actions,
+            headers,
+            tasks_container,
+            self.view_run_queue()
+        ]
+        .spacing(0)
+        .width(Length::Fill);
 
         let mut page = container(content)
             .width(Length::Fill)
@@ -364,65 +776,1055 @@ impl TaskScheduler {
                 .center_x()
                 .center_y();
 
-                page = container(column![page, dialog])
-                    .width(Length::Fill)
-                    .height(Length::Fill);
+                page = container(column![page, dialog])
+                    .width(Length::Fill)
+                    .height(Length::Fill);
+            }
+        }
+
+        page.into()
+    }
+
+    fn view_new_task(&self) -> Element<'_, Message> {
+        let content = container(
+            column![
+                text("New Task").size(24),
+                text_input("Task name", &self.task_name).on_input(Message::TaskNameChanged),
+                pick_list(
+                    &IntervalKind::ALL[..],
+                    Some(self.interval_kind),
+                    Message::IntervalChanged
+                ),
+                self.view_interval_editor(),
+                self.view_schedule_preview(),
+                text_input(
+                    "Time zone, e.g. Europe/Berlin (blank = local)",
+                    &self.timezone
+                )
+                .on_input(Message::TimezoneChanged),
+                text_input(
+                    "Random start delay in seconds (optional)",
+                    &self.jitter_secs
+                )
+                .on_input(Message::JitterChanged),
+                text_input(
+                    "Blackout windows, e.g. 22:00-07:00, weekends (optional)",
+                    &self.blackouts
+                )
+                .on_input(Message::BlackoutsChanged),
+                row![
+                    text("Valid from"),
+                    text_input("YYYY-MM-DD", &self.start_date)
+                        .on_input(Message::StartDateChanged)
+                        .width(Length::Fixed(120.0)),
+                    text("until"),
+                    text_input("YYYY-MM-DD", &self.end_date)
+                        .on_input(Message::EndDateChanged)
+                        .width(Length::Fixed(120.0)),
+                    text("at most"),
+                    text_input("∞", &self.max_runs)
+                        .on_input(Message::MaxRunsChanged)
+                        .width(Length::Fixed(80.0)),
+                    text("runs"),
+                ]
+                .spacing(8)
+                .align_items(Alignment::Center),
+                text_input("Tags, comma-separated (optional)", &self.tags)
+                    .on_input(Message::TagsChanged),
+                text_input(
+                    "Runs after (task names, comma-separated, optional)",
+                    &self.depends_on
+                )
+                .on_input(Message::DependsOnChanged),
+                text_input(
+                    "Command to run, e.g. backup.sh --full (optional)",
+                    &self.command_line
+                )
+                .on_input(Message::CommandChanged),
+                row![
+                    text_input("Working directory (optional)", &self.working_dir)
+                        .on_input(Message::WorkingDirChanged),
+                    text_input("Environment, e.g. KEY=value (optional)", &self.env_vars)
+                        .on_input(Message::EnvChanged),
+                    text_input("Time limit in seconds (optional)", &self.timeout_secs)
+                        .on_input(Message::TimeoutChanged),
+                ]
+                .spacing(8),
+                row![
+                    text("Business days:"),
+                    pick_list(
+                        self.calendar_choices(),
+                        Some(self.calendar.clone()),
+                        Message::CalendarChoiceChanged
+                    ),
+                    text("On holidays:"),
+                    pick_list(
+                        &[HolidayPolicy::Skip, HolidayPolicy::NextBusinessDay][..],
+                        Some(self.holiday_policy),
+                        Message::HolidayPolicyChanged
+                    ),
+                ]
+                .spacing(8)
+                .align_items(Alignment::Center),
+                row![
+                    text("After downtime:"),
+                    pick_list(
+                        &[
+                            MisfirePolicy::Skip,
+                            MisfirePolicy::RunOnce,
+                            MisfirePolicy::RunAll
+                        ][..],
+                        Some(self.misfire),
+                        Message::MisfireChanged
+                    ),
+                    text("Priority:"),
+                    pick_list(
+                        &Priority::ALL[..],
+                        Some(self.priority),
+                        Message::PriorityChanged
+                    ),
+                ]
+                .spacing(8)
+                .align_items(Alignment::Center),
+                row![
+                    button("Cancel").on_press(Message::SwitchTo(Screen::Overview)),
+                    button("Add").on_press(Message::AddTask)
+                ]
+                .spacing(10)
+            ]
+            .spacing(10)
+            .width(Length::Fill),
+        )
+        .width(Length::Fill)
+        .padding(20)
+        .center_x();
+
+        content.into()
+    }
+
+    fn view_interval_editor(&self) -> Element<'_, Message> {
+        match self.interval_kind {
+            IntervalKind::Describe => column![
+                text_input(
+                    "e.g. every 2 hours, weekdays at 9am, first Monday of the month",
+                    &self.phrase
+                )
+                .on_input(Message::PhraseChanged),
+                text(match self.selected_interval() {
+                    Ok(interval) => format!("Understood as: {}", interval),
+                    Err(_) => "Also accepts cron expressions".into(),
+                })
+                .size(12)
+                .style(iced::theme::Text::Color([0.5, 0.5, 0.5].into())),
+            ]
+            .spacing(4)
+            .into(),
+            IntervalKind::Cron => column![
+                text_input("Cron expression, e.g. 0 9 * * MON-FRI", &self.cron_expr)
+                    .on_input(Message::CronExprChanged),
+                text("minute hour day-of-month month day-of-week (optional leading seconds)")
+                    .size(12)
+                    .style(iced::theme::Text::Color([0.5, 0.5, 0.5].into())),
+            ]
+            .spacing(4)
+            .into(),
+            IntervalKind::Every => row![
+                text("Every"),
+                text_input("15", &self.every_amount)
+                    .on_input(Message::EveryAmountChanged)
+                    .width(Length::Fixed(80.0)),
+                pick_list(
+                    &PeriodUnit::ALL[..],
+                    Some(self.every_unit),
+                    Message::EveryUnitChanged
+                ),
+            ]
+            .spacing(8)
+            .align_items(Alignment::Center)
+            .into(),
+            IntervalKind::At => {
+                let days = WEEKDAYS
+                    .into_iter()
+                    .map(|day| {
+                        checkbox(day.to_string(), self.at_weekdays.contains(&day))
+                            .on_toggle(move |_| Message::ToggleAtWeekday(day))
+                            .into()
+                    })
+                    .collect::<Vec<Element<Message>>>();
+                column![
+                    self.view_time_of_day_input(),
+                    row(days).spacing(8),
+                    text("Leave all days unchecked to run every day")
+                        .size(12)
+                        .style(iced::theme::Text::Color([0.5, 0.5, 0.5].into())),
+                ]
+                .spacing(4)
+                .into()
+            }
+            IntervalKind::Monthly => {
+                let day: Element<Message> = if self.monthly_last_weekday {
+                    row![
+                        text("On the last"),
+                        pick_list(
+                            &WEEKDAYS[..],
+                            Some(self.monthly_weekday),
+                            Message::MonthlyWeekdayChanged
+                        ),
+                    ]
+                    .spacing(8)
+                    .align_items(Alignment::Center)
+                    .into()
+                } else {
+                    row![
+                        text("On day"),
+                        text_input("1-31", &self.month_day)
+                            .on_input(Message::MonthDayChanged)
+                            .width(Length::Fixed(80.0)),
+                    ]
+                    .spacing(8)
+                    .align_items(Alignment::Center)
+                    .into()
+                };
+                column![
+                    checkbox("Last weekday of the month", self.monthly_last_weekday)
+                        .on_toggle(Message::ToggleMonthlyLastWeekday),
+                    day,
+                    self.view_time_of_day_input(),
+                    text("Days past the end of a month run on its last day (31 -> Apr 30)")
+                        .size(12)
+                        .style(iced::theme::Text::Color([0.5, 0.5, 0.5].into())),
+                ]
+                .spacing(4)
+                .into()
+            }
+            IntervalKind::Yearly => column![
+                row![
+                    text("On"),
+                    pick_list(
+                        &MonthChoice::ALL[..],
+                        Some(self.yearly_month),
+                        Message::YearlyMonthChanged
+                    ),
+                    text_input("Day", &self.month_day)
+                        .on_input(Message::MonthDayChanged)
+                        .width(Length::Fixed(80.0)),
+                ]
+                .spacing(8)
+                .align_items(Alignment::Center),
+                self.view_time_of_day_input(),
+                text("Feb 29 runs on Feb 28 in non-leap years")
+                    .size(12)
+                    .style(iced::theme::Text::Color([0.5, 0.5, 0.5].into())),
+            ]
+            .spacing(4)
+            .into(),
+            IntervalKind::Once => column![
+                row![
+                    text("On"),
+                    text_input("YYYY-MM-DD", &self.once_date)
+                        .on_input(Message::OnceDateChanged)
+                        .width(Length::Fixed(120.0)),
+                ]
+                .spacing(8)
+                .align_items(Alignment::Center),
+                self.view_time_of_day_input(),
+                text("Runs a single time, then disables itself")
+                    .size(12)
+                    .style(iced::theme::Text::Color([0.5, 0.5, 0.5].into())),
+            ]
+            .spacing(4)
+            .into(),
+            _ => column![].into(),
+        }
+    }
+
+    // The next few runs of the task as currently entered, so the schedule can be
+    // checked before it is added.
+    fn view_schedule_preview(&self) -> Element<'_, Message> {
+        let now = self.clock.now();
+        // Attach the business calendar so holiday rules show up in the preview
+        let task = self.selected_task().and_then(|mut task| {
+            attach_calendars(std::slice::from_mut(&mut task), &self.settings.calendars)?;
+            Ok(task)
+        });
+        let lines: Vec<Element<Message>> = match task {
+            Ok(task) => {
+                let runs: Vec<Element<Message>> = task
+                    .upcoming(now)
+                    .take(5)
+                    .map(|at| {
+                        let label = if at <= now {
+                            "Now, as soon as it is added".to_string()
+                        } else {
+                            at.format("%a %Y-%m-%d %H:%M").to_string()
+                        };
+                        text(label).size(14).into()
+                    })
+                    .collect();
+                if runs.is_empty() {
+                    vec![text("Never runs with these settings").size(14).into()]
+                } else {
+                    runs
+                }
+            }
+            Err(e) => vec![text(e.to_string())
+                .size(14)
+                .style(iced::theme::Text::Color([0.8, 0.0, 0.0].into()))
+                .into()],
+        };
+        column![
+            text("Next runs").size(16),
+            column(lines).spacing(2).padding([0, 0, 0, 10]),
+        ]
+        .spacing(4)
+        .into()
+    }
+
+    fn view_time_of_day_input(&self) -> Element<'_, Message> {
+        row![
+            text("At"),
+            text_input("HH:MM", &self.at_time)
+                .on_input(Message::AtTimeChanged)
+                .width(Length::Fixed(80.0)),
+        ]
+        .spacing(8)
+        .align_items(Alignment::Center)
+        .into()
+    }
+
+    fn selected_time_of_day(&self) -> Result<NaiveTime, anyhow::Error> {
+        NaiveTime::parse_from_str(self.at_time.trim(), "%H:%M")
+            .map_err(|_| anyhow!("'{}' is not a time in HH:MM format", self.at_time))
+    }
+
+    fn selected_month_day(&self) -> Result<u32, anyhow::Error> {
+        self.month_day
+            .trim()
+            .parse()
+            .map_err(|_| anyhow!("'{}' is not a day of the month", self.month_day))
+    }
+
+    fn selected_timezone(&self) -> Result<Option<Tz>, anyhow::Error> {
+        let name = self.timezone.trim();
+        if name.is_empty() {
+            return Ok(None);
+        }
+        name.parse::<Tz>()
+            .map(Some)
+            .map_err(|_| anyhow!("'{}' is not an IANA time zone", name))
+    }
+
+    fn selected_task(&self) -> Result<Task, anyhow::Error> {
+        let mut task = Task::new(self.task_name.trim(), self.selected_interval()?);
+        task.timezone = self.selected_timezone()?;
+        task.misfire = self.misfire;
+        let jitter = self.jitter_secs.trim();
+        if !jitter.is_empty() {
+            let secs: u64 = jitter
+                .parse()
+                .map_err(|_| anyhow!("'{}' is not a whole number of seconds", jitter))?;
+            task.jitter = Some(Duration::from_secs(secs)).filter(|d| !d.is_zero());
+        }
+        task.blackouts = parse_blackouts(&self.blackouts)?;
+        task.start_date = parse_optional_date(&self.start_date)?;
+        task.end_date = parse_optional_date(&self.end_date)?;
+        if let (Some(start), Some(end)) = (task.start_date, task.end_date) {
+            if end < start {
+                return Err(anyhow!("the end date is before the start date"));
+            }
+        }
+        task.depends_on = self
+            .depends_on
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect();
+        // Runs on every preview, so only looks up the task's own dependencies;
+        // `add_selected_task` checks for cycles
+        validate_new_task(&task, |name| Some(&self.tasks[self.task_position(name)?]))?;
+        task.priority = self.priority;
+        task.tags = self
+            .tags
+            .split(',')
+            .map(|tag| tag.trim().trim_start_matches('#').to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        task.calendar = self.calendar.0.clone();
+        task.holiday_policy = self.holiday_policy;
+        task.action = Action::from_command_line(&self.command_line)?;
+        let working_dir = self.working_dir.trim();
+        let env = parse_env(&self.env_vars)?;
+        let timeout = self.timeout_secs.trim();
+        let timeout = match timeout {
+            "" => None,
+            secs => match secs.parse::<u64>() {
+                Ok(secs) if secs > 0 => Some(Duration::from_secs(secs)),
+                _ => return Err(anyhow!("'{}' is not a positive number of seconds", secs)),
+            },
+        };
+        match &mut task.action {
+            Some(action) => {
+                action.working_dir =
+                    Some(PathBuf::from(working_dir)).filter(|_| !working_dir.is_empty());
+                action.env = env;
+                action.timeout = timeout;
+            }
+            None if !working_dir.is_empty() || !env.is_empty() || timeout.is_some() => {
+                return Err(anyhow!(
+                    "enter a command to use a working directory, environment or time limit"
+                ));
+            }
+            None => {}
+        }
+        let max_runs = self.max_runs.trim();
+        if !max_runs.is_empty() {
+            task.max_runs = Some(
+                max_runs
+                    .parse()
+                    .map_err(|_| anyhow!("'{}' is not a whole number of runs", max_runs))?,
+            );
+        }
+        Ok(task)
+    }
+
+    fn calendar_choices(&self) -> Vec<CalendarChoice> {
+        std::iter::once(CalendarChoice(None))
+            .chain(
+                self.settings
+                    .calendars
+                    .iter()
+                    .map(|c| CalendarChoice(Some(c.name.clone()))),
+            )
+            .collect()
+    }
+
+    fn selected_interval(&self) -> Result<Interval, anyhow::Error> {
+        match self.interval_kind {
+            IntervalKind::Hourly => Ok(Interval::Hourly),
+            IntervalKind::Daily => Ok(Interval::Daily),
+            IntervalKind::Weekly => Ok(Interval::Weekly),
+            IntervalKind::Every => {
+                let amount: u64 = self
+                    .every_amount
+                    .trim()
+                    .parse()
+                    .map_err(|_| anyhow!("'{}' is not a whole number", self.every_amount))?;
+                let interval = Interval::Every(Duration::from_secs(
+                    amount.saturating_mul(self.every_unit.seconds()),
+                ));
+                interval.validate()?;
+                Ok(interval)
+            }
+            IntervalKind::At => {
+                let time = self.selected_time_of_day()?;
+                let mut weekdays = self.at_weekdays.clone();
+                weekdays.sort_by_key(|d| d.num_days_from_monday());
+                Ok(Interval::At { time, weekdays })
+            }
+            IntervalKind::Monthly => {
+                let day = if self.monthly_last_weekday {
+                    MonthDay::Last(self.monthly_weekday)
+                } else {
+                    MonthDay::Day(self.selected_month_day()?)
+                };
+                let interval = Interval::Monthly {
+                    day,
+                    time: self.selected_time_of_day()?,
+                };
+                interval.validate()?;
+                Ok(interval)
+            }
+            IntervalKind::Yearly => {
+                let interval = Interval::Yearly {
+                    month: self.yearly_month.0,
+                    day: self.selected_month_day()?,
+                    time: self.selected_time_of_day()?,
+                };
+                interval.validate()?;
+                Ok(interval)
+            }
+            IntervalKind::Once => {
+                let date =
+                    NaiveDate::parse_from_str(self.once_date.trim(), "%Y-%m-%d").map_err(|_| {
+                        anyhow!("'{}' is not a date in YYYY-MM-DD format", self.once_date)
+                    })?;
+                let naive = date.and_time(self.selected_time_of_day()?);
+                // Interpret the date and time in the task's zone when one is given
+                let at = match self.selected_timezone()? {
+                    Some(tz) => naive
+                        .and_local_timezone(tz)
+                        .earliest()
+                        .map(|dt| dt.with_timezone(&Local)),
+                    None => naive.and_local_timezone(Local).earliest(),
+                }
+                .ok_or_else(|| anyhow!("that time does not exist in the chosen time zone"))?;
+                Ok(Interval::Once(at))
+            }
+            IntervalKind::Cron => Interval::cron(&self.cron_expr),
+            IntervalKind::Describe => match self.selected_timezone()? {
+                Some(tz) => parse_phrase(&self.phrase, &self.clock.now().with_timezone(&tz)),
+                None => parse_phrase(&self.phrase, &self.clock.now()),
+            },
+        }
+    }
+
+    fn view_history(&self) -> Element<'_, Message> {
+        let content = container(
+            column![
+                text("History").size(24),
+                scrollable(
+                    column(
+                        self.history
+                            .iter()
+                            .enumerate()
+                            .map(|(idx, h)| {
+                                match self.run_outputs.get(&(self.history_dropped + idx)) {
+                                    Some((stdout, stderr)) => {
+                                        let mut line =
+                                            row![text(h)].spacing(8).align_items(Alignment::Center);
+                                        for (label, path) in
+                                            [("stdout", stdout), ("stderr", stderr)]
+                                        {
+                                            if let Some(path) = path {
+                                                line = line.push(
+                                                    button(text(label).size(12)).on_press(
+                                                        Message::ShowRunOutput(path.clone()),
+                                                    ),
+                                                );
+                                            }
+                                        }
+                                        line.into()
+                                    }
+                                    None => text(h).into(),
+                                }
+                            })
+                            .collect::<Vec<Element<Message>>>(),
+                    )
+                    .spacing(5)
+                )
+                .height(Length::Fill),
+                self.view_run_output(),
+                button("Back").on_press(Message::SwitchTo(Screen::Overview))
+            ]
+            .spacing(10)
+            .width(Length::Fill),
+        )
+        .width(Length::Fill)
+        .padding(20)
+        .center_x();
+
+        content.into()
+    }
+
+    // The run output opened from the history, if any
+    fn view_run_output(&self) -> Element<'_, Message> {
+        let Some((path, contents)) = &self.output_view else {
+            return column![].into();
+        };
+        let body = match contents {
+            Ok(output) => text(output).size(12),
+            Err(e) => text(format!("Could not read the output: {}", e))
+                .style(iced::theme::Text::Color([0.8, 0.0, 0.0].into())),
+        };
+        column![
+            row![
+                text(path.display().to_string()).size(12),
+                button("Close").on_press(Message::CloseRunOutput),
+            ]
+            .spacing(8)
+            .align_items(Alignment::Center),
+            scrollable(body).height(Length::Fixed(240.0)),
+        ]
+        .spacing(4)
+        .into()
+    }
+
+    fn view_dry_run(&self) -> Element<'_, Message> {
+        let (summary, rows): (String, Vec<Element<Message>>) = match &self.dry_run {
+            None => (
+                "Simulate the schedule to see every run before it happens".into(),
+                vec![],
+            ),
+            Some((from, simulation)) => {
+                let runs = &simulation.runs;
+                let rows = runs
+                    .iter()
+                    .take(MAX_DRY_RUN_ROWS)
+                    .map(|run| {
+                        let mut line =
+                            format!("{}  {}", run.at.format("%a %Y-%m-%d %H:%M"), run.task);
+                        if run.scheduled < run.at {
+                            line.push_str(&format!(
+                                "  (due {})",
+                                run.scheduled.format("%a %Y-%m-%d %H:%M")
+                            ));
+                        }
+                        text(line).into()
+                    })
+                    .collect();
+                let until = *from + self.dry_run_horizon.duration();
+                let mut summary = format!(
+                    "{} runs between {} and {}",
+                    runs.len(),
+                    from.format("%Y-%m-%d %H:%M"),
+                    until.format("%Y-%m-%d %H:%M")
+                );
+                if runs.len() > MAX_DRY_RUN_ROWS {
+                    summary.push_str(&format!(" (showing the first {})", MAX_DRY_RUN_ROWS));
+                }
+                if let Some(stopped) = simulation.stopped_at {
+                    summary.push_str(&format!(
+                        ". Too many runs to simulate: stopped at {}, later runs are not counted",
+                        stopped.format("%Y-%m-%d %H:%M:%S")
+                    ));
+                }
+                (summary, rows)
+            }
+        };
+
+        let content = container(
+            column![
+                text("Dry Run").size(24),
+                row![
+                    pick_list(
+                        &DryRunHorizon::ALL[..],
+                        Some(self.dry_run_horizon),
+                        Message::DryRunHorizonChanged
+                    ),
+                    button("Simulate").on_press(Message::RunDryRun),
+                ]
+                .spacing(8)
+                .align_items(Alignment::Center),
+                text(summary),
+                text("Random start delays, spreading and the concurrency limit are not simulated")
+                    .size(12)
+                    .style(iced::theme::Text::Color([0.5, 0.5, 0.5].into())),
+                scrollable(column(rows).spacing(5)).height(Length::Fill),
+            ]
+            .spacing(10)
+            .width(Length::Fill),
+        )
+        .width(Length::Fill)
+        .padding(20)
+        .center_x();
+
+        content.into()
+    }
+
+    fn view_settings(&self) -> Element<'_, Message> {
+        let content = container(
+            column![
+                text("Settings").size(24),
+                checkbox("Spread due tasks", self.settings.spread_window.is_some())
+                    .on_toggle(Message::ToggleSpread),
+                text("Blackout windows (apply to every task)"),
+                row![
+                    text_input("e.g. 22:00-07:00, weekends", &self.global_blackouts)
+                        .on_input(Message::GlobalBlackoutsChanged)
+                        .on_submit(Message::ApplyGlobalBlackouts),
+                    button("Apply").on_press(Message::ApplyGlobalBlackouts),
+                ]
+                .spacing(8),
+                text("Comma-separated: HH:MM-HH:MM, Sat/Sun, weekdays, or Mon/Fri 12:00-13:00")
+                    .size(12)
+                    .style(iced::theme::Text::Color([0.5, 0.5, 0.5].into())),
+                row![
+                    text("Max concurrent runs"),
+                    text_input("Unlimited", &self.max_concurrent)
+                        .on_input(Message::MaxConcurrentChanged)
+                        .on_submit(Message::ApplyMaxConcurrent)
+                        .width(Length::Fixed(100.0)),
+                    button("Apply").on_press(Message::ApplyMaxConcurrent),
+                ]
+                .spacing(8)
+                .align_items(Alignment::Center),
+                text("Tasks due beyond the limit queue by priority")
+                    .size(12)
+                    .style(iced::theme::Text::Color([0.5, 0.5, 0.5].into())),
+                text("Business calendars"),
+                self.view_calendars(),
+                row![
+                    text_input("Calendar name", &self.calendar_name)
+                        .on_input(Message::CalendarNameChanged)
+                        .width(Length::FillPortion(1)),
+                    text_input(
+                        "Holiday file: .ics or one YYYY-MM-DD per line (optional)",
+                        &self.calendar_file
+                    )
+                    .on_input(Message::CalendarFileChanged)
+                    .width(Length::FillPortion(2)),
+                    checkbox("Weekdays only", self.calendar_weekdays_only)
+                        .on_toggle(Message::ToggleCalendarWeekdaysOnly),
+                    button("Add calendar").on_press(Message::AddCalendar),
+                ]
+                .spacing(8)
+                .align_items(Alignment::Center),
+                button("Back").on_press(Message::SwitchTo(Screen::Overview))
+            ]
+            .spacing(10)
+            .width(Length::Fill),
+        )
+        .width(Length::Fill)
+        .padding(20)
+        .center_x();
+
+        content.into()
+    }
+
+    fn view_calendars(&self) -> Element<'_, Message> {
+        let rows = self
+            .settings
+            .calendars
+            .iter()
+            .enumerate()
+            .map(|(idx, calendar)| {
+                let mut summary = format!("{} holidays", calendar.holidays.len());
+                if calendar.weekdays_only {
+                    summary.push_str(", weekdays only");
+                }
+                if let Some(source) = &calendar.source {
+                    summary.push_str(&format!(", from {}", source.display()));
+                }
+                row![
+                    text(&calendar.name).width(Length::FillPortion(1)),
+                    text(summary).width(Length::FillPortion(3)),
+                    button("Delete").on_press(Message::DeleteCalendar(idx)),
+                ]
+                .spacing(8)
+                .align_items(Alignment::Center)
+                .into()
+            })
+            .collect::<Vec<Element<Message>>>();
+        column(rows).spacing(4).into()
+    }
+
+    fn save_settings_cmd(&self) -> Command<Message> {
+        let settings = self.settings.clone();
+        Command::perform(
+            async move { save_settings(&settings).await.map_err(|e| e.to_string()) },
+            Message::SaveResult,
+        )
+    }
+}
+
+// ---------- Scheduling ----------
+impl TaskScheduler {
+    // When the next check is needed. A task that is still due after a check is
+    // looked at again a second later at the earliest.
+    fn next_wakeup(&self) -> Option<chrono::DateTime<Local>> {
+        let at = self.scheduler.next_wakeup()?;
+        Some(match self.last_check {
+            Some(last) => at.max(last + chrono::Duration::seconds(1)),
+            None => at,
+        })
+    }
+
+    // Queues the runs reported due by the scheduler, after any start delay, and
+    // logs the tasks held back by a blackout window.
+    fn start_due_runs(&mut self, updates: Vec<(usize, DueRuns)>) -> Command<Message> {
+        let updates: Vec<(usize, DueRuns)> = updates
+            .into_iter()
+            .filter(|(idx, _)| {
+                *idx < self.tasks.len()
+                    && !self.delayed_runs.contains(&self.tasks[*idx].name)
+                    && !self.run_queue.contains(&self.tasks[*idx].name)
+            })
+            .collect();
+        let (deferred, updates): (Vec<_>, Vec<_>) = updates
+            .into_iter()
+            .partition(|(_, due)| due.deferred.is_some());
+        self.record_deferrals(&deferred);
+        let due_tasks: Vec<&Task> = updates.iter().map(|(idx, _)| &self.tasks[*idx]).collect();
+        let delays = start_delays(
+            &due_tasks,
+            self.settings.spread_window,
+            &mut rand::thread_rng(),
+        );
+
+        let now = self.clock.now();
+        let mut delayed = Vec::new();
+        for ((idx, due), delay) in updates.into_iter().zip(delays) {
+            if delay.is_zero() {
+                self.run_queue.push(&self.tasks[idx], due, now);
+            } else {
+                let name = self.tasks[idx].name.clone();
+                self.history.push(format!(
+                    "[{}] Task '{}' starts in {}s",
+                    self.clock.now().format("%H:%M:%S"),
+                    name,
+                    delay.as_secs()
+                ));
+                self.delayed_runs.insert(name.clone());
+                delayed.push(Command::perform(tokio::time::sleep(delay), move |_| {
+                    Message::RunDelayed(name.clone(), due.clone())
+                }));
+            }
+        }
+        delayed.push(self.start_ready_runs());
+        Command::batch(delayed)
+    }
+
+    // Records a check's runs and skips in history and updates the task's state
+    fn apply_due_runs(&mut self, idx: usize, due: &DueRuns) {
+        let now = self.clock.now();
+        let name = self.tasks[idx].name.clone();
+        for missed in &due.skipped {
+            self.history.push(format!(
+                "[{}] Task '{}' missed run at {} skipped",
+                now.format("%H:%M:%S"),
+                name,
+                missed.format("%Y-%m-%d %H:%M:%S")
+            ));
+        }
+        if due.truncated {
+            self.history.push(format!(
+                "[{}] Task '{}' missed more runs than can be listed",
+                now.format("%H:%M:%S"),
+                name
+            ));
+        }
+        let verb = match &self.tasks[idx].action {
+            Some(action) => {
+                info!("Starting task '{}': {}", name, action);
+                format!("started: {}", action)
+            }
+            None => "checked".to_string(),
+        };
+        let catch_up = due.run.len() > 1 || self.catch_up_runs.contains_key(&name);
+        for scheduled in &due.run {
+            if catch_up {
+                self.history.push(format!(
+                    "[{}] Task '{}' {} (catch-up for {})",
+                    now.format("%H:%M:%S"),
+                    name,
+                    verb,
+                    scheduled.format("%Y-%m-%d %H:%M:%S")
+                ));
+            } else {
+                self.history.push(format!(
+                    "[{}] Task '{}' {}",
+                    now.format("%H:%M:%S"),
+                    name,
+                    verb
+                ));
+            }
+        }
+        match (due.run.is_empty(), due.skipped.last()) {
+            (false, _) => {
+                for _ in &due.run {
+                    self.tasks[idx].mark_complete(now);
+                }
+            }
+            (true, Some(last)) => self.tasks[idx].mark_skipped(*last),
+            (true, None) => {}
+        }
+        if self.tasks[idx].is_completed() {
+            self.history.push(format!(
+                "[{}] One-shot task '{}' completed and disabled",
+                now.format("%H:%M:%S"),
+                name
+            ));
+        } else if !due.run.is_empty() && self.tasks[idx].is_expired(now) {
+            self.history.push(format!(
+                "[{}] Task '{}' reached its run limit and expired",
+                now.format("%H:%M:%S"),
+                name
+            ));
+        }
+        self.scheduler.update(self.tasks[idx].clone(), now);
+    }
+
+    // Starts queued runs while slots are free. Each run executes the task's action
+    // once in the background and finishes on a later message, so that its slot stays
+    // taken until the program exits. Further catch-up occurrences wait their turn
+    // in `catch_up_runs`.
+    fn start_ready_runs(&mut self) -> Command<Message> {
+        let mut commands = Vec::new();
+        for mut run in self.run_queue.start_ready() {
+            match self.task_position(&run.task) {
+                Some(idx) if self.tasks[idx].enabled => {
+                    if run.due.run.len() > 1 {
+                        let rest = run.due.run.split_off(1);
+                        self.catch_up_runs.insert(run.task.clone(), rest);
+                    }
+                    self.apply_due_runs(idx, &run.due);
+                    let name = run.task.clone();
+                    let action = self.tasks[idx].action.clone();
+                    let runs = !run.due.run.is_empty();
+                    let clock = self.clock.clone();
+                    let capture = run_output_dir().map(|dir| OutputCapture {
+                        dir,
+                        name: name.clone(),
+                        max_bytes: MAX_RUN_OUTPUT_BYTES,
+                    });
+                    commands.push(Command::perform(
+                        async move {
+                            // A check that only skipped runs executes nothing
+                            let action = action.filter(|_| runs)?;
+                            let run = action.run(&*clock, capture.as_ref()).await;
+                            let saved = run.stdout.is_some() || run.stderr.is_some();
+                            if let (true, Some(capture)) = (saved, &capture) {
+                                if let Err(e) =
+                                    prune_run_output(&capture.dir, MAX_RUN_OUTPUT_TOTAL_BYTES)
+                                {
+                                    error!("Pruning run output failed: {:#}", e);
+                                }
+                            }
+                            Some(run)
+                        },
+                        move |run| Message::RunFinished(name.clone(), run),
+                    ));
+                }
+                // Deleted or disabled while it waited
+                _ => {
+                    self.run_queue.finish(&run.task);
+                    self.catch_up_runs.remove(&run.task);
+                }
             }
         }
+        if !commands.is_empty() {
+            commands.push(self.save_cmd());
+        }
+        Command::batch(commands)
+    }
 
-        page.into()
+    // Drops the oldest history lines, and their output links, past the limit
+    fn trim_history(&mut self) {
+        let Some(excess) = self.history.len().checked_sub(MAX_HISTORY_LINES) else {
+            return;
+        };
+        self.history.drain(..excess);
+        self.history_dropped += excess;
+        let first = self.history_dropped;
+        self.run_outputs.retain(|line, _| *line >= first);
     }
 
-    fn view_new_task(&self) -> Element<'_, Message> {
-        let content = container(
-            column![
-                text("New Task").size(24),
-                text_input("Task name", &self.task_name).on_input(Message::TaskNameChanged),
-                pick_list(
-                    vec![Interval::Hourly, Interval::Daily, Interval::Weekly],
-                    Some(self.interval),
-                    Message::IntervalChanged
-                ),
-                row![
-                    button("Cancel").on_press(Message::SwitchTo(Screen::Overview)),
-                    button("Add").on_press(Message::AddTask)
-                ]
-                .spacing(10)
-            ]
-            .spacing(10)
-            .width(Length::Fill),
-        )
-        .width(Length::Fill)
-        .padding(20)
-        .center_x();
+    // Logs how each execution of a task's action ended
+    fn record_action_runs(&mut self, name: &str, runs: &[RunRecord]) {
+        for run in runs {
+            let secs = (run.finished - run.started).num_seconds();
+            let line = match &run.status {
+                status if status.is_success() => {
+                    info!("Task '{}' finished in {}s", name, secs);
+                    format!("Task '{}' finished in {}s", name, secs)
+                }
+                RunStatus::Failed(e) => {
+                    error!("Task '{}' could not run: {}", name, e);
+                    format!("Task '{}' could not run: {}", name, e)
+                }
+                status @ RunStatus::TimedOut(_) => {
+                    error!("Task '{}' {}", name, status);
+                    format!("Task '{}' {} and was stopped after {}s", name, status, secs)
+                }
+                status => {
+                    error!("Task '{}' failed after {}s ({})", name, secs, status);
+                    format!("Task '{}' failed after {}s ({})", name, secs, status)
+                }
+            };
+            self.history
+                .push(format!("[{}] {}", run.finished.format("%H:%M:%S"), line));
+            if run.stdout.is_some() || run.stderr.is_some() {
+                self.run_outputs.insert(
+                    self.history_dropped + self.history.len() - 1,
+                    (run.stdout.clone(), run.stderr.clone()),
+                );
+            }
+            if let Some(e) = &run.output_error {
+                error!("Saving output of task '{}' failed: {}", name, e);
+                self.history.push(format!(
+                    "[{}] Could not save the output of task '{}': {}",
+                    run.finished.format("%H:%M:%S"),
+                    name,
+                    e
+                ));
+            }
+        }
+    }
 
-        content.into()
+    // Re-resolves every task's business calendar after tasks or calendars change,
+    // then re-queues the tasks since their due times depend on it
+    fn refresh_calendars(&mut self) {
+        self.attach_task_calendars();
+        self.sync_scheduler();
     }
 
-    fn view_history(&self) -> Element<'_, Message> {
-        let content = container(
-            column![
-                text("History").size(24),
-                scrollable(
-                    column(
-                        self.history
-                            .iter()
-                            .map(|h| text(h).into())
-                            .collect::<Vec<Element<Message>>>(),
-                    )
-                    .spacing(5)
-                )
-                .height(Length::Fill),
-                button("Back").on_press(Message::SwitchTo(Screen::Overview))
-            ]
-            .spacing(10)
-            .width(Length::Fill),
-        )
-        .width(Length::Fill)
-        .padding(20)
-        .center_x();
+    // Resolves every task's business calendar, logging tasks whose calendar is missing
+    fn attach_task_calendars(&mut self) {
+        if let Err(e) = attach_calendars(&mut self.tasks, &self.settings.calendars) {
+            error!("{}", e);
+            self.history.push(format!(
+                "[{}] {}; those tasks run on any day",
+                self.clock.now().format("%H:%M:%S"),
+                e
+            ));
+        }
+    }
 
-        content.into()
+    // Adds the task described by the form, rejecting it if it would close a
+    // dependency cycle
+    fn add_selected_task(&mut self) -> Result<(), anyhow::Error> {
+        let mut task = self.selected_task()?;
+        attach_calendars(std::slice::from_mut(&mut task), &self.settings.calendars)?;
+        self.tasks.push(task);
+        if let Err(e) = validate_dependencies(&self.tasks) {
+            self.tasks.pop();
+            return Err(e);
+        }
+        let idx = self.tasks.len() - 1;
+        self.task_index.insert(self.tasks[idx].name.clone(), idx);
+        self.scheduler
+            .update(self.tasks[idx].clone(), self.clock.now());
+        Ok(())
+    }
+
+    fn task_position(&self, name: &str) -> Option<usize> {
+        self.task_index.get(name).copied()
+    }
+
+    // Rebuilds `task_index` after the whole task list was replaced. Loading rejects
+    // files that repeat a name, so every task gets its own entry.
+    fn reindex_tasks(&mut self) {
+        self.task_index = self
+            .tasks
+            .iter()
+            .enumerate()
+            .map(|(idx, task)| (task.name.clone(), idx))
+            .collect();
+    }
+
+    // Rebuilds the scheduler from the task list after changes to many tasks
+    fn sync_scheduler(&mut self) {
+        self.scheduler = Scheduler::with_tasks(
+            self.tasks.iter().cloned(),
+            self.settings.blackouts.clone(),
+            self.clock.now(),
+        );
+    }
+
+    // Logs tasks held back by a blackout. The scheduler reports each deferral once
+    // and checks the task again when the window ends.
+    fn record_deferrals(&mut self, deferred: &[(usize, DueRuns)]) {
+        for (idx, due) in deferred {
+            let Some(deferral) = &due.deferred else {
+                continue;
+            };
+            self.history.push(format!(
+                "[{}] Task '{}' deferred until {} ({})",
+                self.clock.now().format("%H:%M:%S"),
+                self.tasks[*idx].name,
+                deferral.until.format("%Y-%m-%d %H:%M"),
+                deferral
+            ));
+        }
+    }
+
+    fn save_cmd(&self) -> Command<Message> {
+        let tasks_clone = self.tasks.clone();
+        Command::perform(
+            async move { replace_tasks(&tasks_clone).await.map_err(|e| e.to_string()) },
+            Message::SaveResult,
+        )
     }
 }
 
@@ -465,12 +1867,60 @@ impl Application for TaskScheduler {
                 .init();
         }
 
+        let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+        let today = clock.now().format("%Y-%m-%d").to_string();
         let app = Self {
+            clock,
             screen: Screen::Overview,
             tasks: vec![],
+            task_index: HashMap::new(),
             task_name: String::new(),
-            interval: Interval::Daily,
+            interval_kind: IntervalKind::Daily,
+            cron_expr: String::new(),
+            phrase: String::new(),
+            every_amount: "15".into(),
+            every_unit: PeriodUnit::Minutes,
+            at_time: "09:00".into(),
+            at_weekdays: Vec::new(),
+            month_day: "1".into(),
+            monthly_last_weekday: false,
+            monthly_weekday: Weekday::Fri,
+            yearly_month: MonthChoice(1),
+            once_date: today,
+            timezone: String::new(),
+            misfire: MisfirePolicy::default(),
+            jitter_secs: String::new(),
+            blackouts: String::new(),
+            start_date: String::new(),
+            end_date: String::new(),
+            max_runs: String::new(),
+            calendar: CalendarChoice(None),
+            holiday_policy: HolidayPolicy::default(),
+            depends_on: String::new(),
+            command_line: String::new(),
+            working_dir: String::new(),
+            env_vars: String::new(),
+            timeout_secs: String::new(),
+            priority: Priority::default(),
+            tags: String::new(),
+            tag_filter: TagFilter(None),
+            dry_run_horizon: DryRunHorizon::Week,
+            dry_run: None,
+            settings: Settings::default(),
+            global_blackouts: String::new(),
+            calendar_name: String::new(),
+            calendar_file: String::new(),
+            calendar_weekdays_only: true,
+            max_concurrent: String::new(),
+            run_queue: RunQueue::new(None),
+            delayed_runs: HashSet::new(),
+            catch_up_runs: HashMap::new(),
+            last_check: None,
+            scheduler: Scheduler::default(),
             history: vec!["App started.".into()],
+            history_dropped: 0,
+            run_outputs: HashMap::new(),
+            output_view: None,
             is_saving: false,
             is_loading: false,
             pending_delete: None,
@@ -482,10 +1932,16 @@ impl Application for TaskScheduler {
 
         (
             app,
-            Command::perform(
-                async { load_tasks_cmd().await.map_err(|e| e.to_string()) },
-                Message::LoadResult,
-            ),
+            Command::batch([
+                Command::perform(
+                    async { load_tasks().await.map_err(|e| e.to_string()) },
+                    Message::LoadResult,
+                ),
+                Command::perform(
+                    async { load_settings().await.map_err(|e| e.to_string()) },
+                    Message::SettingsLoaded,
+                ),
+            ]),
         )
     }
 
@@ -494,10 +1950,29 @@ impl Application for TaskScheduler {
     }
 
     fn subscription(&self) -> Subscription<Message> {
-        let mut subs: Vec<Subscription<Message>> = vec![
-            time::every(Duration::from_secs(60)).map(|_| Message::Tick),
-            keyboard::on_key_press(|key, modifiers| Some(Message::KeyPress { key, modifiers })),
-        ];
+        let mut subs: Vec<Subscription<Message>> =
+            vec![keyboard::on_key_press(|key, modifiers| {
+                Some(Message::KeyPress { key, modifiers })
+            })];
+
+        // Sleep until the earliest task falls due. The timer is keyed on its wakeup
+        // time, so any change that moves the wakeup replaces it with a new one.
+        if let Some(at) = self.next_wakeup() {
+            let clock = self.clock.clone();
+            subs.push(iced::subscription::run_with_id(
+                ("wakeup", at),
+                iced::futures::stream::once(async move {
+                    loop {
+                        let left = (at - clock.now()).to_std().unwrap_or_default();
+                        if left.is_zero() {
+                            break;
+                        }
+                        tokio::time::sleep(left.min(MAX_TIMER_SLEEP)).await;
+                    }
+                    Message::Tick
+                }),
+            ));
+        }
 
         // Small tick used to drive toast expiration
         subs.push(time::every(Duration::from_millis(500)).map(|_| Message::ToastTick));
@@ -520,6 +1995,8 @@ impl Application for TaskScheduler {
             button("Overview").on_press(Message::SwitchTo(Screen::Overview)),
             button("New Task").on_press(Message::SwitchTo(Screen::NewTask)),
             button("History").on_press(Message::SwitchTo(Screen::History)),
+            button("Settings").on_press(Message::SwitchTo(Screen::Settings)),
+            button("Dry Run").on_press(Message::SwitchTo(Screen::DryRun)),
             button("Save").on_press(Message::Save),
             button("Load").on_press(Message::Load),
             button(if self.is_dark {
@@ -574,6 +2051,8 @@ impl Application for TaskScheduler {
             Screen::Overview => self.view_overview(),
             Screen::NewTask => self.view_new_task(),
             Screen::History => self.view_history(),
+            Screen::Settings => self.view_settings(),
+            Screen::DryRun => self.view_dry_run(),
         };
 
         container(
@@ -589,37 +2068,56 @@ impl Application for TaskScheduler {
     }
 
     fn update(&mut self, msg: Message) -> Command<Message> {
+        self.trim_history();
         match msg {
             Message::Tick => {
-                self.history.push(format!(
-                    "[{}] Checking tasks...",
-                    Local::now().format("%H:%M:%S")
-                ));
-                let tasks = self.tasks.clone();
-                Command::perform(check_tasks(tasks), Message::TaskCheckComplete)
+                let now = self.clock.now();
+                self.last_check = Some(now);
+                debug!("Checking tasks");
+                let events = self.scheduler.poll(now);
+                let updates: Vec<(usize, DueRuns)> = events
+                    .into_iter()
+                    .filter_map(|event| Some((self.task_position(&event.task)?, event.due)))
+                    .collect();
+                self.start_due_runs(updates)
             }
-            Message::TaskCheckComplete(updates) => {
-                let mut any_updates = false;
-                for (idx, _) in updates {
-                    if idx < self.tasks.len() {
-                        any_updates = true;
-                        self.tasks[idx].last_run = Some(Local::now());
-                        self.history.push(format!(
-                            "[{}] Task '{}' checked",
-                            Local::now().format("%H:%M:%S"),
-                            self.tasks[idx].name
-                        ));
+            Message::RunDelayed(name, due) => {
+                self.delayed_runs.remove(&name);
+                // The task may have been deleted or disabled while waiting
+                match self.task_position(&name) {
+                    Some(idx) if self.tasks[idx].enabled => {
+                        self.run_queue.push(&self.tasks[idx], due, self.clock.now());
+                        self.start_ready_runs()
                     }
+                    _ => Command::none(),
                 }
-                if any_updates {
-                    let tasks_clone = self.tasks.clone();
-                    Command::perform(
-                        async move { save_tasks_cmd(tasks_clone).await.map_err(|e| e.to_string()) },
-                        Message::SaveResult,
-                    )
-                } else {
-                    Command::none()
+            }
+            Message::RunFinished(name, run) => {
+                self.record_action_runs(&name, run.as_slice());
+                self.run_queue.finish(&name);
+                let catch_up = self.catch_up_runs.remove(&name);
+                let mut commands = Vec::new();
+                if let Some(idx) = self.task_position(&name) {
+                    let now = self.clock.now();
+                    if let Some(run) = run {
+                        self.tasks[idx].record_outcome(run);
+                        commands.push(self.save_cmd());
+                    }
+                    // The next catch-up run queues behind any higher-priority tasks
+                    if let (Some(rest), true) = (catch_up, self.tasks[idx].enabled) {
+                        let due = DueRuns {
+                            run: rest,
+                            ..DueRuns::default()
+                        };
+                        self.run_queue.push(&self.tasks[idx], due, now);
+                    }
+                    // A task that fell due again while it was running was passed
+                    // over; queue it again now that it may start. A successful run
+                    // also releases the tasks that run after it.
+                    self.scheduler.update(self.tasks[idx].clone(), now);
                 }
+                commands.push(self.start_ready_runs());
+                Command::batch(commands)
             }
             Message::SwitchTo(s) => {
                 self.screen = s;
@@ -647,17 +2145,396 @@ impl Application for TaskScheduler {
                 Command::none()
             }
 
-            Message::IntervalChanged(interval) => {
-                self.interval = interval;
+            Message::IntervalChanged(kind) => {
+                self.interval_kind = kind;
+                Command::none()
+            }
+            Message::CronExprChanged(expr) => {
+                self.cron_expr = expr;
+                Command::none()
+            }
+            Message::PhraseChanged(phrase) => {
+                self.phrase = phrase;
+                Command::none()
+            }
+            Message::EveryAmountChanged(amount) => {
+                // Only accept digits so the field always holds a parseable count
+                if amount.chars().all(|c| c.is_ascii_digit()) {
+                    self.every_amount = amount;
+                }
+                Command::none()
+            }
+            Message::EveryUnitChanged(unit) => {
+                self.every_unit = unit;
+                Command::none()
+            }
+            Message::AtTimeChanged(time) => {
+                self.at_time = time;
+                Command::none()
+            }
+            Message::MonthDayChanged(day) => {
+                if day.chars().all(|c| c.is_ascii_digit()) {
+                    self.month_day = day;
+                }
+                Command::none()
+            }
+            Message::ToggleMonthlyLastWeekday(last) => {
+                self.monthly_last_weekday = last;
+                Command::none()
+            }
+            Message::MonthlyWeekdayChanged(day) => {
+                self.monthly_weekday = day;
+                Command::none()
+            }
+            Message::YearlyMonthChanged(month) => {
+                self.yearly_month = month;
+                Command::none()
+            }
+            Message::OnceDateChanged(date) => {
+                self.once_date = date;
+                Command::none()
+            }
+            Message::TimezoneChanged(tz) => {
+                self.timezone = tz;
+                Command::none()
+            }
+            Message::MisfireChanged(policy) => {
+                self.misfire = policy;
+                Command::none()
+            }
+            Message::JitterChanged(secs) => {
+                if secs.chars().all(|c| c.is_ascii_digit()) {
+                    self.jitter_secs = secs;
+                }
+                Command::none()
+            }
+            Message::ToggleSpread(spread) => {
+                self.settings.spread_window = spread.then_some(SPREAD_WINDOW);
+                self.save_settings_cmd()
+            }
+            Message::BlackoutsChanged(blackouts) => {
+                self.blackouts = blackouts;
+                Command::none()
+            }
+            Message::PriorityChanged(priority) => {
+                self.priority = priority;
+                Command::none()
+            }
+            Message::MaxConcurrentChanged(max) => {
+                if max.chars().all(|c| c.is_ascii_digit()) {
+                    self.max_concurrent = max;
+                }
+                Command::none()
+            }
+            Message::ApplyMaxConcurrent => {
+                let max = match self.max_concurrent.trim() {
+                    "" => None,
+                    digits => match digits.parse::<usize>() {
+                        Ok(max) if max > 0 => Some(max),
+                        _ => {
+                            self.toasts.push((
+                                "At least one task must be able to run".into(),
+                                Instant::now(),
+                            ));
+                            return Command::none();
+                        }
+                    },
+                };
+                self.settings.max_concurrent = max;
+                self.run_queue.set_max_concurrent(max);
+                self.toasts
+                    .push(("Concurrency limit updated".into(), Instant::now()));
+                Command::batch([self.save_settings_cmd(), self.start_ready_runs()])
+            }
+            Message::Snooze(idx, preset) => {
+                if idx >= self.tasks.len() {
+                    return Command::none();
+                }
+                let until = preset.until(self.clock.now());
+                let task = &mut self.tasks[idx];
+                task.paused_until = until;
+                let entry = match until {
+                    Some(until) => format!(
+                        "Snoozed '{}' until {}",
+                        task.name,
+                        until.format("%Y-%m-%d %H:%M")
+                    ),
+                    None => format!("Resumed '{}'", task.name),
+                };
+                if until.is_some() {
+                    let name = task.name.clone();
+                    self.run_queue.remove(&name);
+                }
+                self.scheduler.update(task.clone(), self.clock.now());
+                self.history.push(format!(
+                    "[{}] {}",
+                    self.clock.now().format("%H:%M:%S"),
+                    entry
+                ));
+                self.toasts.push((entry, Instant::now()));
+                self.save_cmd()
+            }
+            Message::TagsChanged(tags) => {
+                self.tags = tags;
+                Command::none()
+            }
+            Message::TagFilterChanged(filter) => {
+                self.tag_filter = filter;
+                Command::none()
+            }
+            Message::DryRunHorizonChanged(horizon) => {
+                self.dry_run_horizon = horizon;
+                self.update(Message::RunDryRun)
+            }
+            Message::RunDryRun => {
+                let now = self.clock.now();
+                let until = now + self.dry_run_horizon.duration();
+                let tasks = self.tasks.clone();
+                let blackouts = self.settings.blackouts.clone();
+                Command::perform(
+                    async move { (now, simulate(&tasks, now, until, &blackouts)) },
+                    |(from, simulation)| Message::DryRunComplete(from, simulation),
+                )
+            }
+            Message::DryRunComplete(from, simulation) => {
+                self.dry_run = Some((from, simulation));
+                Command::none()
+            }
+            Message::ShowRunOutput(path) => {
+                let file = path.clone();
+                Command::perform(
+                    async move {
+                        fs::read(&file)
+                            .map(|data| String::from_utf8_lossy(&data).into_owned())
+                            .map_err(|e| e.to_string())
+                    },
+                    move |contents| Message::RunOutputLoaded(path.clone(), contents),
+                )
+            }
+            Message::RunOutputLoaded(path, contents) => {
+                self.output_view = Some((path, contents));
+                Command::none()
+            }
+            Message::CloseRunOutput => {
+                self.output_view = None;
+                Command::none()
+            }
+            Message::SetTagEnabled(tag, enabled) => {
+                let changed = set_tag_enabled(&mut self.tasks, &tag, enabled);
+                let now = self.clock.now();
+                for task in self.tasks.iter().filter(|t| t.tags.contains(&tag)) {
+                    if !enabled {
+                        self.run_queue.remove(&task.name);
+                    }
+                    self.scheduler.update(task.clone(), now);
+                }
+                let action = if enabled { "Enabled" } else { "Disabled" };
+                self.history.push(format!(
+                    "[{}] {} {} tasks tagged #{}",
+                    self.clock.now().format("%H:%M:%S"),
+                    action,
+                    changed,
+                    tag
+                ));
+                self.toasts
+                    .push((format!("{} {} tasks", action, changed), Instant::now()));
+                self.save_cmd()
+            }
+            Message::CommandChanged(line) => {
+                self.command_line = line;
+                Command::none()
+            }
+            Message::WorkingDirChanged(dir) => {
+                self.working_dir = dir;
+                Command::none()
+            }
+            Message::TimeoutChanged(secs) => {
+                self.timeout_secs = secs;
+                Command::none()
+            }
+            Message::EnvChanged(vars) => {
+                self.env_vars = vars;
+                Command::none()
+            }
+            Message::DependsOnChanged(names) => {
+                self.depends_on = names;
+                Command::none()
+            }
+            Message::CalendarChoiceChanged(choice) => {
+                self.calendar = choice;
+                Command::none()
+            }
+            Message::HolidayPolicyChanged(policy) => {
+                self.holiday_policy = policy;
+                Command::none()
+            }
+            Message::CalendarNameChanged(name) => {
+                self.calendar_name = name;
+                Command::none()
+            }
+            Message::CalendarFileChanged(path) => {
+                self.calendar_file = path;
+                Command::none()
+            }
+            Message::ToggleCalendarWeekdaysOnly(weekdays_only) => {
+                self.calendar_weekdays_only = weekdays_only;
+                Command::none()
+            }
+            Message::AddCalendar => {
+                let name = self.calendar_name.trim().to_string();
+                if name.is_empty() {
+                    return Command::none();
+                }
+                if self.settings.calendars.iter().any(|c| c.name == name) {
+                    self.toasts.push((
+                        format!("A calendar named '{}' already exists", name),
+                        Instant::now(),
+                    ));
+                    return Command::none();
+                }
+                let mut calendar = BusinessCalendar::new(name);
+                calendar.weekdays_only = self.calendar_weekdays_only;
+                let file = self.calendar_file.trim();
+                if file.is_empty() {
+                    return self.update(Message::CalendarLoaded(Ok(calendar)));
+                }
+                let path = PathBuf::from(file);
+                Command::perform(
+                    async move {
+                        let holidays = load_holidays(&path).map_err(|e| format!("{:#}", e))?;
+                        calendar.holidays.extend(holidays);
+                        calendar.source = Some(path);
+                        Ok(calendar)
+                    },
+                    Message::CalendarLoaded,
+                )
+            }
+            Message::CalendarLoaded(res) => match res {
+                Ok(calendar) => {
+                    self.history.push(format!(
+                        "[{}] Added calendar '{}' ({} holidays)",
+                        self.clock.now().format("%H:%M:%S"),
+                        calendar.name,
+                        calendar.holidays.len()
+                    ));
+                    self.settings.calendars.push(calendar);
+                    self.calendar_name.clear();
+                    self.calendar_file.clear();
+                    self.refresh_calendars();
+                    self.toasts.push(("Calendar added".into(), Instant::now()));
+                    self.save_settings_cmd()
+                }
+                Err(e) => {
+                    error!("Loading calendar failed: {}", e);
+                    self.history.push(format!(
+                        "[{}] Loading calendar failed: {}",
+                        self.clock.now().format("%H:%M:%S"),
+                        e
+                    ));
+                    self.toasts.push((e, Instant::now()));
+                    Command::none()
+                }
+            },
+            Message::DeleteCalendar(idx) => {
+                if idx < self.settings.calendars.len() {
+                    let removed = self.settings.calendars.remove(idx);
+                    if self.calendar.0.as_ref() == Some(&removed.name) {
+                        self.calendar = CalendarChoice(None);
+                    }
+                    self.history.push(format!(
+                        "[{}] Deleted calendar '{}'",
+                        self.clock.now().format("%H:%M:%S"),
+                        removed.name
+                    ));
+                    self.refresh_calendars();
+                    self.save_settings_cmd()
+                } else {
+                    Command::none()
+                }
+            }
+            Message::StartDateChanged(date) => {
+                self.start_date = date;
+                Command::none()
+            }
+            Message::EndDateChanged(date) => {
+                self.end_date = date;
+                Command::none()
+            }
+            Message::MaxRunsChanged(runs) => {
+                if runs.chars().all(|c| c.is_ascii_digit()) {
+                    self.max_runs = runs;
+                }
+                Command::none()
+            }
+            Message::GlobalBlackoutsChanged(blackouts) => {
+                self.global_blackouts = blackouts;
+                Command::none()
+            }
+            Message::ApplyGlobalBlackouts => match parse_blackouts(&self.global_blackouts) {
+                Ok(blackouts) => {
+                    self.settings.blackouts = blackouts;
+                    self.global_blackouts = format_blackouts(&self.settings.blackouts);
+                    self.scheduler
+                        .set_blackouts(self.settings.blackouts.clone(), self.clock.now());
+                    self.toasts
+                        .push(("Blackout windows updated".into(), Instant::now()));
+                    self.save_settings_cmd()
+                }
+                Err(e) => {
+                    let msg = format!("{:#}", e);
+                    error!("Invalid blackout windows: {}", msg);
+                    self.toasts.push((msg, Instant::now()));
+                    Command::none()
+                }
+            },
+            Message::SettingsLoaded(res) => {
+                match res {
+                    Ok(settings) => {
+                        self.global_blackouts = format_blackouts(&settings.blackouts);
+                        self.max_concurrent = settings
+                            .max_concurrent
+                            .map(|max| max.to_string())
+                            .unwrap_or_default();
+                        self.run_queue.set_max_concurrent(settings.max_concurrent);
+                        self.settings = settings;
+                        self.refresh_calendars();
+                    }
+                    Err(e) => {
+                        error!("Loading settings failed: {}", e);
+                        self.history.push(format!(
+                            "[{}] Loading settings failed: {}",
+                            self.clock.now().format("%H:%M:%S"),
+                            e
+                        ));
+                    }
+                }
+                Command::none()
+            }
+            Message::ToggleAtWeekday(day) => {
+                if let Some(pos) = self.at_weekdays.iter().position(|d| *d == day) {
+                    self.at_weekdays.remove(pos);
+                } else {
+                    self.at_weekdays.push(day);
+                }
                 Command::none()
             }
             Message::AddTask => {
                 if !self.task_name.trim().is_empty() {
-                    let task = Task::new(self.task_name.trim(), self.interval);
-                    self.tasks.push(task);
+                    if let Err(e) = self.add_selected_task() {
+                        let msg = format!("{:#}", e);
+                        error!("Invalid task: {}", msg);
+                        self.history.push(format!(
+                            "[{}] Could not add '{}': {}",
+                            self.clock.now().format("%H:%M:%S"),
+                            self.task_name.trim(),
+                            msg
+                        ));
+                        self.toasts.push((msg, Instant::now()));
+                        return Command::none();
+                    }
                     self.history.push(format!(
                         "[{}] Added '{}'",
-                        Local::now().format("%H:%M:%S"),
+                        self.clock.now().format("%H:%M:%S"),
                         self.task_name
                     ));
                     self.task_name.clear();
@@ -666,7 +2543,7 @@ impl Application for TaskScheduler {
                     self.toasts.push(("Task added".into(), Instant::now()));
                     let tasks_clone = self.tasks.clone();
                     Command::perform(
-                        async move { save_tasks_cmd(tasks_clone).await.map_err(|e| e.to_string()) },
+                        async move { replace_tasks(&tasks_clone).await.map_err(|e| e.to_string()) },
                         Message::SaveResult,
                     )
                 } else {
@@ -681,16 +2558,31 @@ impl Application for TaskScheduler {
                 if let Some(idx) = self.pending_delete.take() {
                     if idx < self.tasks.len() {
                         let removed = self.tasks.remove(idx);
+                        self.run_queue.remove(&removed.name);
+                        self.task_index.remove(&removed.name);
+                        for pos in self.task_index.values_mut().filter(|pos| **pos > idx) {
+                            *pos -= 1;
+                        }
+                        let now = self.clock.now();
+                        self.scheduler.remove(&removed.name, now);
+                        // Downstream tasks no longer wait for the deleted task
+                        for task in &mut self.tasks {
+                            let before = task.depends_on.len();
+                            task.depends_on.retain(|name| *name != removed.name);
+                            if task.depends_on.len() != before {
+                                self.scheduler.update(task.clone(), now);
+                            }
+                        }
                         self.history.push(format!(
                             "[{}] Deleted '{}'",
-                            Local::now().format("%H:%M:%S"),
+                            self.clock.now().format("%H:%M:%S"),
                             removed.name
                         ));
                         // show toast for deletion and save
                         self.toasts.push(("Task deleted".into(), Instant::now()));
                         let tasks_clone = self.tasks.clone();
                         Command::perform(
-                            async move { save_tasks_cmd(tasks_clone).await.map_err(|e| e.to_string()) },
+                            async move { replace_tasks(&tasks_clone).await.map_err(|e| e.to_string()) },
                             Message::SaveResult,
                         )
                     } else {
@@ -709,18 +2601,18 @@ impl Application for TaskScheduler {
                 if let Some(p) = project_file_path() {
                     self.history.push(format!(
                         "[{}] Saving to {}...",
-                        Local::now().format("%H:%M:%S"),
+                        self.clock.now().format("%H:%M:%S"),
                         p.display()
                     ));
                 } else {
                     self.history.push(format!(
                         "[{}] Saving: no config dir",
-                        Local::now().format("%H:%M:%S")
+                        self.clock.now().format("%H:%M:%S")
                     ));
                 }
                 let tasks_clone = self.tasks.clone();
                 Command::perform(
-                    async move { save_tasks_cmd(tasks_clone).await.map_err(|e| e.to_string()) },
+                    async move { replace_tasks(&tasks_clone).await.map_err(|e| e.to_string()) },
                     Message::SaveResult,
                 )
             }
@@ -755,14 +2647,14 @@ impl Application for TaskScheduler {
                         info!("Save successful");
                         self.history.push(format!(
                             "[{}] Save successful",
-                            Local::now().format("%H:%M:%S")
+                            self.clock.now().format("%H:%M:%S")
                         ));
                     }
                     Err(e) => {
                         error!("Save failed: {}", e);
                         self.history.push(format!(
                             "[{}] Save failed: {}",
-                            Local::now().format("%H:%M:%S"),
+                            self.clock.now().format("%H:%M:%S"),
                             e
                         ));
                     }
@@ -774,17 +2666,17 @@ impl Application for TaskScheduler {
                 if let Some(p) = project_file_path() {
                     self.history.push(format!(
                         "[{}] Loading from {}...",
-                        Local::now().format("%H:%M:%S"),
+                        self.clock.now().format("%H:%M:%S"),
                         p.display()
                     ));
                 } else {
                     self.history.push(format!(
                         "[{}] Loading: no config dir",
-                        Local::now().format("%H:%M:%S")
+                        self.clock.now().format("%H:%M:%S")
                     ));
                 }
                 Command::perform(
-                    async { load_tasks_cmd().await.map_err(|e| e.to_string()) },
+                    async { load_tasks().await.map_err(|e| e.to_string()) },
                     Message::LoadResult,
                 )
             }
@@ -793,11 +2685,19 @@ impl Application for TaskScheduler {
                 match res {
                     Ok(list) => {
                         let count = list.len();
-                        self.tasks = list;
+                        let now = self.clock.now();
+                        for old in std::mem::replace(&mut self.tasks, list) {
+                            self.scheduler.remove(&old.name, now);
+                        }
+                        self.reindex_tasks();
+                        self.attach_task_calendars();
+                        for task in &self.tasks {
+                            self.scheduler.update(task.clone(), now);
+                        }
                         info!("Load successful ({} tasks)", count);
                         self.history.push(format!(
                             "[{}] Loaded tasks ({} items)",
-                            Local::now().format("%H:%M:%S"),
+                            self.clock.now().format("%H:%M:%S"),
                             count
                         ));
                     }
@@ -805,7 +2705,7 @@ impl Application for TaskScheduler {
                         error!("Load failed: {}", e);
                         self.history.push(format!(
                             "[{}] Load failed: {}",
-                            Local::now().format("%H:%M:%S"),
+                            self.clock.now().format("%H:%M:%S"),
                             e
                         ));
                     }
@@ -816,9 +2716,14 @@ impl Application for TaskScheduler {
             Message::ToggleEnable(idx) => {
                 if idx < self.tasks.len() {
                     self.tasks[idx].enabled = !self.tasks[idx].enabled;
+                    if !self.tasks[idx].enabled {
+                        self.run_queue.remove(&self.tasks[idx].name);
+                    }
+                    self.scheduler
+                        .update(self.tasks[idx].clone(), self.clock.now());
                     let tasks_clone = self.tasks.clone();
                     Command::perform(
-                        async move { save_tasks_cmd(tasks_clone).await.map_err(|e| e.to_string()) },
+                        async move { replace_tasks(&tasks_clone).await.map_err(|e| e.to_string()) },
                         Message::SaveResult,
                     )
                 } else {
@@ -0,0 +1,428 @@
+use anyhow::anyhow;
+use chrono::{DateTime, Local, Month, NaiveDate, NaiveTime, TimeZone, Weekday};
+use std::time::Duration;
+
+use crate::{unit_seconds, Interval, MonthDay};
+
+const EXAMPLES: &str =
+    "try \"every 2 hours\", \"weekdays at 9am\" or \"first Monday of the month\"";
+
+// Words that carry no meaning once the time of day has been taken out
+const FILLERS: [&str; 7] = ["on", "the", "of", "and", "each", "a", "an"];
+
+const ORDINAL_WORDS: [&str; 4] = ["first", "second", "third", "fourth"];
+
+/// Parses an everyday description of a schedule into an [`Interval`], e.g.
+/// "every 2 hours", "weekdays at 9am", "first Monday of the month at 10:30",
+/// "on the 15th of every month", "every year on Mar 3" or "tomorrow at noon".
+///
+/// The `Display` form of an interval and plain cron expressions are accepted too.
+/// Dates such as "tomorrow" are read in `now`'s time zone.
+pub fn parse_phrase<Z: TimeZone>(
+    input: &str,
+    now: &DateTime<Z>,
+) -> Result<Interval, anyhow::Error> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(anyhow!("describe when the task should run; {}", EXAMPLES));
+    }
+    let interval = parse_words(input, now)
+        .or_else(|| input.parse().ok())
+        .or_else(|| Interval::cron(input).ok())
+        .ok_or_else(|| match month_count(input) {
+            Some(count) => anyhow!(
+                "every {} months is not supported; try a cron expression such as \"0 0 1 */{} *\"",
+                count,
+                count
+            ),
+            None => anyhow!("couldn't understand '{}'; {}", input, EXAMPLES),
+        })?;
+    interval.validate()?;
+    // A one-shot time that has already gone by would fire the moment it is added
+    if let Interval::Once(at) = &interval {
+        if *at <= now.with_timezone(&Local) {
+            return Err(anyhow!(
+                "{} has already passed; pick a later time",
+                at.format("%Y-%m-%d %H:%M")
+            ));
+        }
+    }
+    Ok(interval)
+}
+
+fn parse_words<Z: TimeZone>(input: &str, now: &DateTime<Z>) -> Option<Interval> {
+    let lower = input.to_lowercase().replace([',', '/'], " ");
+    let mut words: Vec<String> = Vec::new();
+    for word in lower.split_whitespace() {
+        // "9 am" -> "9am"
+        match (word, words.last_mut()) {
+            ("am" | "pm", Some(last)) if last.chars().all(|c| c.is_ascii_digit() || c == ':') => {
+                last.push_str(word)
+            }
+            _ => words.push(word.to_string()),
+        }
+    }
+
+    // Take out the time of day, then the filler words around it
+    let mut time = None;
+    let mut every = false;
+    // "on" or "the" before a bare number makes it a day of the month
+    let mut dated = false;
+    let mut rule = Vec::new();
+    let mut iter = words.iter().map(String::as_str);
+    while let Some(word) = iter.next() {
+        let at = if word == "at" {
+            Some(parse_time(iter.next()?, true)?)
+        } else {
+            parse_time(word, false)
+        };
+        if let Some(at) = at {
+            if time.replace(at).is_some() {
+                return None;
+            }
+            continue;
+        }
+        match word {
+            "every" => every = true,
+            word if FILLERS.contains(&word) => dated |= matches!(word, "on" | "the"),
+            word => rule.push(word),
+        }
+    }
+
+    parse_once(&rule, time, now)
+        .or_else(|| parse_repeat(&rule, time))
+        .or_else(|| parse_weekdays(&rule, time))
+        .or_else(|| parse_monthly(&rule, time, dated))
+        .or_else(|| parse_yearly(&rule, time, every))
+}
+
+// "9am", "9:30pm", "21:00", "noon" and "midnight". A bare hour such as "9" only
+// counts after "at", so "every 2 hours" keeps its number.
+fn parse_time(word: &str, after_at: bool) -> Option<NaiveTime> {
+    match word {
+        "noon" | "midday" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return Some(NaiveTime::MIN),
+        _ => {}
+    }
+    let (clock, pm) = match (word.strip_suffix("am"), word.strip_suffix("pm")) {
+        (Some(clock), _) => (clock, Some(false)),
+        (_, Some(clock)) => (clock, Some(true)),
+        _ => (word, None),
+    };
+    if pm.is_none() && !after_at && !clock.contains(':') {
+        return None;
+    }
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) if minute.len() == 2 => (hour.parse().ok()?, minute.parse().ok()?),
+        Some(_) => return None,
+        None => (clock.parse::<u32>().ok()?, 0),
+    };
+    let hour = match pm {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some(pm) => hour % 12 + if pm { 12 } else { 0 },
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+// "1st", "15th", "15", or "first" to "fourth".
+fn parse_ordinal(word: &str) -> Option<u32> {
+    if let Some(i) = ORDINAL_WORDS.iter().position(|w| *w == word) {
+        return Some(i as u32 + 1);
+    }
+    let digits = ["st", "nd", "rd", "th"]
+        .iter()
+        .find_map(|suffix| word.strip_suffix(suffix))
+        .unwrap_or(word);
+    digits.parse().ok()
+}
+
+// "mon", "monday" or "mondays".
+fn parse_weekday(word: &str) -> Option<Weekday> {
+    word.parse()
+        .ok()
+        .or_else(|| word.strip_suffix('s')?.parse().ok())
+}
+
+// "tomorrow at 9am", "today at 17:00" or "once on 2024-05-01 at noon".
+fn parse_once<Z: TimeZone>(
+    rule: &[&str],
+    time: Option<NaiveTime>,
+    now: &DateTime<Z>,
+) -> Option<Interval> {
+    let today = now.date_naive();
+    let mut date = None;
+    for word in rule {
+        let day = match *word {
+            "once" => continue,
+            "today" => today,
+            "tomorrow" => today.succ_opt()?,
+            word => NaiveDate::parse_from_str(word, "%Y-%m-%d").ok()?,
+        };
+        if date.replace(day).is_some() {
+            return None;
+        }
+    }
+    let naive = date?.and_time(time.unwrap_or(NaiveTime::MIN));
+    let at = now.timezone().from_local_datetime(&naive).earliest()?;
+    Some(Interval::Once(at.with_timezone(&Local)))
+}
+
+// "hourly", "daily at 9am", "every 2 hours", "every other week".
+fn parse_repeat(rule: &[&str], time: Option<NaiveTime>) -> Option<Interval> {
+    let (count, unit) = match rule {
+        ["hourly"] => (1, "hour"),
+        ["daily"] => (1, "day"),
+        ["weekly"] => (1, "week"),
+        [unit] => (1, *unit),
+        ["other", unit] => (2, *unit),
+        [count, unit] => (count.parse().ok()?, *unit),
+        _ => return None,
+    };
+    const HOUR: u64 = 60 * 60;
+    const DAY: u64 = 24 * HOUR;
+    const WEEK: u64 = 7 * DAY;
+    match (count, unit_seconds(unit)?, time) {
+        (1, DAY, Some(time)) => Some(Interval::At {
+            time,
+            weekdays: vec![],
+        }),
+        // Only whole days have a time of day
+        (_, _, Some(_)) => None,
+        (1, DAY, None) => Some(Interval::Daily),
+        (1, WEEK, None) => Some(Interval::Weekly),
+        (1, HOUR, None) => Some(Interval::Hourly),
+        (count, unit_secs, None) => Some(Interval::Every(Duration::from_secs(
+            unit_secs.checked_mul(count)?,
+        ))),
+    }
+}
+
+// "weekdays at 9am", "every Monday and Thursday at 18:00", "weekends".
+fn parse_weekdays(rule: &[&str], time: Option<NaiveTime>) -> Option<Interval> {
+    if rule.is_empty() {
+        return None;
+    }
+    let mut weekdays = Vec::new();
+    for word in rule {
+        match *word {
+            "weekday" | "weekdays" => weekdays.extend([
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ]),
+            "weekend" | "weekends" => weekdays.extend([Weekday::Sat, Weekday::Sun]),
+            word => weekdays.push(parse_weekday(word)?),
+        }
+    }
+    weekdays.sort_by_key(|d| d.num_days_from_monday());
+    weekdays.dedup();
+    Some(Interval::At {
+        time: time.unwrap_or(NaiveTime::MIN),
+        weekdays,
+    })
+}
+
+// "first Monday of the month", "last Friday of every month at 17:00",
+// "on the 15th of every month", "monthly on day 1", "last day of the month".
+// A bare number is only a day with `dated`, so "every 3 months" isn't the 3rd.
+fn parse_monthly(rule: &[&str], time: Option<NaiveTime>, dated: bool) -> Option<Interval> {
+    let is_month = |w: &&str| matches!(*w, "month" | "months" | "monthly");
+    if !rule.iter().any(is_month) {
+        return None;
+    }
+    let rest: Vec<&str> = rule.iter().copied().filter(|w| !is_month(w)).collect();
+    let day = match rest.as_slice() {
+        [] => MonthDay::Day(1),
+        ["last", "day"] => MonthDay::Day(31),
+        ["last", weekday] => MonthDay::Last(parse_weekday(weekday)?),
+        [day] if dated || !day.chars().all(|c| c.is_ascii_digit()) => {
+            MonthDay::Day(parse_ordinal(day)?)
+        }
+        [day, "day"] | ["day", day] => MonthDay::Day(parse_ordinal(day)?),
+        [n, weekday] => MonthDay::Nth(parse_ordinal(n)?, parse_weekday(weekday)?),
+        _ => return None,
+    };
+    Some(Interval::Monthly {
+        day,
+        time: time.unwrap_or(NaiveTime::MIN),
+    })
+}
+
+// The count in "every 3 months", which monthly schedules can't repeat by.
+fn month_count(input: &str) -> Option<u32> {
+    let lower = input.to_lowercase();
+    let words: Vec<&str> = lower.split_whitespace().collect();
+    words.windows(2).find_map(|pair| match pair {
+        [count, "month" | "months"] => count.parse().ok(),
+        _ => None,
+    })
+}
+
+// "every year on Mar 3", "yearly on December 25th at 8am", "every 1st of April".
+fn parse_yearly(rule: &[&str], time: Option<NaiveTime>, every: bool) -> Option<Interval> {
+    let is_year = |w: &&str| matches!(*w, "year" | "years" | "yearly" | "annually");
+    // A bare date such as "Mar 3" needs "every" so it isn't mistaken for a one-off
+    if !every && !rule.iter().any(is_year) {
+        return None;
+    }
+    let rest: Vec<&str> = rule.iter().copied().filter(|w| !is_year(w)).collect();
+    let (month, day) = match rest.as_slice() {
+        [a, b] => match (a.parse::<Month>(), b.parse::<Month>()) {
+            (Ok(month), _) => (month, parse_ordinal(b)?),
+            (_, Ok(month)) => (month, parse_ordinal(a)?),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(Interval::Yearly {
+        month: month.number_from_month(),
+        day,
+        time: time.unwrap_or(NaiveTime::MIN),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<Interval, anyhow::Error> {
+        let now = Local.with_ymd_and_hms(2024, 5, 8, 10, 0, 0).unwrap();
+        parse_phrase(s, &now)
+    }
+
+    fn hm(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_repeating_phrases() {
+        assert_eq!(
+            parse("every 2 hours").unwrap(),
+            Interval::Every(Duration::from_secs(2 * 60 * 60))
+        );
+        assert_eq!(
+            parse("Every 15 mins").unwrap(),
+            Interval::Every(Duration::from_secs(15 * 60))
+        );
+        assert_eq!(
+            parse("every other day").unwrap(),
+            Interval::Every(Duration::from_secs(2 * 24 * 60 * 60))
+        );
+        assert_eq!(parse("every hour").unwrap(), Interval::Hourly);
+        assert_eq!(parse("weekly").unwrap(), Interval::Weekly);
+        assert_eq!(
+            parse("every day at 6:30pm").unwrap(),
+            Interval::At {
+                time: hm(18, 30),
+                weekdays: vec![]
+            }
+        );
+        assert!(parse("every 2 days at 9am").is_err());
+    }
+
+    #[test]
+    fn test_weekday_phrases() {
+        assert_eq!(
+            parse("weekdays at 9am").unwrap(),
+            Interval::At {
+                time: hm(9, 0),
+                weekdays: vec![
+                    Weekday::Mon,
+                    Weekday::Tue,
+                    Weekday::Wed,
+                    Weekday::Thu,
+                    Weekday::Fri
+                ]
+            }
+        );
+        assert_eq!(
+            parse("Fridays and Mondays at 12am").unwrap(),
+            Interval::At {
+                time: NaiveTime::MIN,
+                weekdays: vec![Weekday::Mon, Weekday::Fri]
+            }
+        );
+        assert_eq!(
+            parse("every Sunday at noon").unwrap(),
+            Interval::At {
+                time: hm(12, 0),
+                weekdays: vec![Weekday::Sun]
+            }
+        );
+    }
+
+    #[test]
+    fn test_monthly_and_yearly_phrases() {
+        assert_eq!(
+            parse("first Monday of the month").unwrap(),
+            Interval::Monthly {
+                day: MonthDay::Nth(1, Weekday::Mon),
+                time: NaiveTime::MIN
+            }
+        );
+        assert_eq!(
+            parse("last friday of every month at 17:00").unwrap(),
+            Interval::Monthly {
+                day: MonthDay::Last(Weekday::Fri),
+                time: hm(17, 0)
+            }
+        );
+        assert_eq!(
+            parse("on the 15th of every month at 8am").unwrap(),
+            Interval::Monthly {
+                day: MonthDay::Day(15),
+                time: hm(8, 0)
+            }
+        );
+        assert_eq!(
+            parse("every year on Mar 3 at 9").unwrap(),
+            Interval::Yearly {
+                month: 3,
+                day: 3,
+                time: hm(9, 0)
+            }
+        );
+        assert_eq!(
+            parse("monthly on the 3").unwrap(),
+            Interval::Monthly {
+                day: MonthDay::Day(3),
+                time: NaiveTime::MIN
+            }
+        );
+        let err = parse("every 3 months").unwrap_err().to_string();
+        assert!(err.contains("every 3 months is not supported"), "{}", err);
+        assert!(
+            parse("0 0 1 */3 *").is_ok(),
+            "the suggested cron expression parses"
+        );
+        assert!(parse("every 6 months at 9am").is_err());
+        assert!(parse("fifth Monday of the month").is_err());
+        assert!(parse("on the 32nd of each month").is_err());
+    }
+
+    #[test]
+    fn test_one_off_and_fallback_phrases() {
+        let tomorrow = Local.with_ymd_and_hms(2024, 5, 9, 9, 0, 0).unwrap();
+        assert_eq!(parse("tomorrow at 9am").unwrap(), Interval::Once(tomorrow));
+        // It is 10:00, so 9am today has gone by
+        let err = parse("today at 9am").unwrap_err().to_string();
+        assert!(err.contains("already passed"), "{}", err);
+        assert!(parse("today at 11am").is_ok());
+        assert_eq!(
+            parse("Monthly on the first Mon at 09:00").unwrap(),
+            Interval::Monthly {
+                day: MonthDay::Nth(1, Weekday::Mon),
+                time: hm(9, 0)
+            }
+        );
+        assert_eq!(
+            parse("0 9 * * MON-FRI").unwrap(),
+            Interval::Cron("0 9 * * MON-FRI".into())
+        );
+        let err = parse("whenever it suits").unwrap_err().to_string();
+        assert!(err.contains("weekdays at 9am"), "{}", err);
+    }
+}
@@ -0,0 +1,183 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::{DueRuns, Task};
+
+/// How urgently a due task starts when it has to queue for a free run slot.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Critical,
+}
+
+impl Priority {
+    pub const ALL: [Priority; 4] = [
+        Priority::Low,
+        Priority::Normal,
+        Priority::High,
+        Priority::Critical,
+    ];
+}
+
+impl std::fmt::Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Priority::Low => write!(f, "Low"),
+            Priority::Normal => write!(f, "Normal"),
+            Priority::High => write!(f, "High"),
+            Priority::Critical => write!(f, "Critical"),
+        }
+    }
+}
+
+/// A due run waiting in a [`RunQueue`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedRun {
+    pub task: String,
+    pub priority: Priority,
+    pub due: DueRuns,
+    pub queued_at: DateTime<Local>,
+}
+
+/// Due runs waiting for one of a limited number of run slots. Runs start in
+/// priority order, first come first served within a priority, and each task is
+/// queued or running at most once.
+#[derive(Debug, Clone, Default)]
+pub struct RunQueue {
+    max_concurrent: Option<usize>,
+    // Kept in start order
+    queued: Vec<QueuedRun>,
+    running: HashSet<String>,
+}
+
+impl RunQueue {
+    /// A queue allowing `max_concurrent` simultaneous runs; `None` is unlimited.
+    pub fn new(max_concurrent: Option<usize>) -> Self {
+        Self {
+            max_concurrent,
+            ..Self::default()
+        }
+    }
+
+    pub fn set_max_concurrent(&mut self, max_concurrent: Option<usize>) {
+        self.max_concurrent = max_concurrent;
+    }
+
+    /// True if the task is waiting or running.
+    pub fn contains(&self, task: &str) -> bool {
+        self.running.contains(task) || self.queued.iter().any(|q| q.task == task)
+    }
+
+    /// Queues a due run of `task`. Returns false, leaving the queue unchanged, if the
+    /// task is already waiting or running.
+    pub fn push(&mut self, task: &Task, due: DueRuns, now: DateTime<Local>) -> bool {
+        if self.contains(&task.name) {
+            return false;
+        }
+        let at = self
+            .queued
+            .iter()
+            .position(|q| q.priority < task.priority)
+            .unwrap_or(self.queued.len());
+        self.queued.insert(
+            at,
+            QueuedRun {
+                task: task.name.clone(),
+                priority: task.priority,
+                due,
+                queued_at: now,
+            },
+        );
+        true
+    }
+
+    /// Takes the runs that can start now that slots are free, marking them running.
+    pub fn start_ready(&mut self) -> Vec<QueuedRun> {
+        let free = match self.max_concurrent {
+            Some(max) => max.saturating_sub(self.running.len()),
+            None => self.queued.len(),
+        };
+        let ready: Vec<QueuedRun> = self.queued.drain(..free.min(self.queued.len())).collect();
+        self.running
+            .extend(ready.iter().map(|run| run.task.clone()));
+        ready
+    }
+
+    /// Frees the slot held by a run of `task`.
+    pub fn finish(&mut self, task: &str) {
+        self.running.remove(task);
+    }
+
+    /// Drops a waiting run, e.g. because its task was deleted or disabled.
+    pub fn remove(&mut self, task: &str) {
+        self.queued.retain(|q| q.task != task);
+    }
+
+    /// Waiting runs in the order they will start.
+    pub fn queued(&self) -> &[QueuedRun] {
+        &self.queued
+    }
+
+    /// Names of the tasks currently running, sorted.
+    pub fn running(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.running.iter().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Interval;
+
+    fn task(name: &str, priority: Priority) -> Task {
+        let mut task = Task::new(name, Interval::Hourly);
+        task.priority = priority;
+        task
+    }
+
+    #[test]
+    fn test_queue_orders_by_priority_then_arrival() {
+        let now = Local::now();
+        let mut queue = RunQueue::new(Some(2));
+        for (name, priority) in [
+            ("Backup", Priority::Low),
+            ("Report", Priority::Normal),
+            ("Alerts", Priority::Critical),
+            ("Export", Priority::Normal),
+        ] {
+            assert!(queue.push(&task(name, priority), DueRuns::default(), now));
+        }
+        assert!(!queue.push(&task("Report", Priority::High), DueRuns::default(), now));
+
+        let started: Vec<String> = queue.start_ready().into_iter().map(|r| r.task).collect();
+        assert_eq!(started, vec!["Alerts", "Report"]);
+        assert!(queue.start_ready().is_empty(), "Both slots are taken");
+        assert_eq!(queue.running(), vec!["Alerts", "Report"]);
+
+        queue.finish("Alerts");
+        let started: Vec<String> = queue.start_ready().into_iter().map(|r| r.task).collect();
+        assert_eq!(started, vec!["Export"]);
+        assert_eq!(queue.queued().len(), 1);
+        assert_eq!(queue.queued()[0].task, "Backup");
+    }
+
+    #[test]
+    fn test_unlimited_queue_starts_everything() {
+        let now = Local::now();
+        let mut queue = RunQueue::new(None);
+        queue.push(&task("A", Priority::Normal), DueRuns::default(), now);
+        queue.push(&task("B", Priority::High), DueRuns::default(), now);
+        assert_eq!(queue.start_ready().len(), 2);
+        assert!(queue.contains("A"));
+        queue.finish("A");
+        assert!(!queue.contains("A"));
+    }
+}
@@ -0,0 +1,333 @@
+use chrono::{DateTime, Local};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::{BlackoutWindow, DueRuns, Task};
+
+/// A task that needs attention, reported by [`Scheduler::poll`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DueEvent {
+    pub task: String,
+    /// The runs to start or skip now, or the blackout window the task is waiting out.
+    pub due: DueRuns,
+}
+
+/// Tasks indexed by the time they next need attention, so finding the due ones
+/// costs a lookup per due task rather than a scan over every task.
+///
+/// A task reported as due by [`poll`](Scheduler::poll) leaves the queue until it is
+/// handed back through [`update`](Scheduler::update), normally after its runs were
+/// recorded with [`Task::mark_complete`] or [`Task::mark_skipped`]. A task waiting
+/// for upstream tasks is queued again when one of them is updated.
+#[derive(Debug, Clone, Default)]
+pub struct Scheduler {
+    tasks: HashMap<String, Task>,
+    // Wakeup time and task name, earliest first
+    queue: BTreeSet<(DateTime<Local>, String)>,
+    wakeups: HashMap<String, DateTime<Local>>,
+    // Upstream task name -> the tasks that run after it
+    dependents: HashMap<String, HashSet<String>>,
+    blackouts: Vec<BlackoutWindow>,
+}
+
+impl Scheduler {
+    /// An empty scheduler applying `global_blackouts` to every task.
+    pub fn new(global_blackouts: Vec<BlackoutWindow>) -> Self {
+        Self {
+            blackouts: global_blackouts,
+            ..Self::default()
+        }
+    }
+
+    /// A scheduler holding `tasks`, queued as of `now`.
+    pub fn with_tasks(
+        tasks: impl IntoIterator<Item = Task>,
+        global_blackouts: Vec<BlackoutWindow>,
+        now: DateTime<Local>,
+    ) -> Self {
+        let mut scheduler = Self::new(global_blackouts);
+        for task in tasks {
+            scheduler.update(task, now);
+        }
+        scheduler
+    }
+
+    pub fn set_blackouts(&mut self, global_blackouts: Vec<BlackoutWindow>, now: DateTime<Local>) {
+        self.blackouts = global_blackouts;
+        let names: Vec<String> = self.tasks.keys().cloned().collect();
+        for name in names {
+            self.schedule(&name, now);
+        }
+    }
+
+    /// Adds a task, or replaces the one with the same name, and queues it as of
+    /// `now`. Tasks that run after it are re-queued if it has just completed, i.e.
+    /// for a task with an action once its run has finished successfully rather
+    /// than when the run started.
+    pub fn update(&mut self, task: Task, now: DateTime<Local>) {
+        let name = task.name.clone();
+        for upstream in &task.depends_on {
+            self.dependents
+                .entry(upstream.clone())
+                .or_default()
+                .insert(name.clone());
+        }
+        let completed = task.last_completed();
+        let old = self.tasks.insert(name.clone(), task);
+        let released = match &old {
+            Some(old) => old.last_completed() != completed,
+            None => true,
+        };
+        if let Some(old) = old {
+            self.forget_dependencies(&old);
+        }
+        self.schedule(&name, now);
+        if released {
+            self.schedule_dependents(&name, now);
+        }
+    }
+
+    /// Removes a task. Tasks that run after it stay blocked until it is added back.
+    pub fn remove(&mut self, name: &str, now: DateTime<Local>) -> Option<Task> {
+        self.unqueue(name);
+        let task = self.tasks.remove(name)?;
+        self.forget_dependencies(&task);
+        self.schedule_dependents(name, now);
+        Some(task)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Task> {
+        self.tasks.get(name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// The earliest time a queued task needs attention, i.e. when to call `poll`.
+    pub fn next_wakeup(&self) -> Option<DateTime<Local>> {
+        self.queue.first().map(|(at, _)| *at)
+    }
+
+    /// Takes every task due at `now` off the queue and reports what each should do,
+    /// in wakeup order. Tasks held back by a blackout window are reported with
+    /// `due.deferred` set and stay queued until the window ends; tasks waiting for
+    /// upstream tasks are not reported.
+    pub fn poll(&mut self, now: DateTime<Local>) -> Vec<DueEvent> {
+        let mut woken = Vec::new();
+        while self.queue.first().is_some_and(|(at, _)| *at <= now) {
+            if let Some((_, name)) = self.queue.pop_first() {
+                self.wakeups.remove(&name);
+                woken.push(name);
+            }
+        }
+
+        let mut events = Vec::new();
+        for name in woken {
+            let Some(task) = self.tasks.get(&name) else {
+                continue;
+            };
+            let due = task.due_runs(now, &self.blackouts);
+            if due.deferred.is_some() || due.is_empty() {
+                self.schedule(&name, now);
+                if due.deferred.is_some() {
+                    events.push(DueEvent { task: name, due });
+                }
+                continue;
+            }
+            if !task.blocked_by_in(|n| self.tasks.get(n)).is_empty() {
+                continue;
+            }
+            events.push(DueEvent { task: name, due });
+        }
+        events
+    }
+
+    fn schedule(&mut self, name: &str, now: DateTime<Local>) {
+        self.unqueue(name);
+        let Some(task) = self.tasks.get(name) else {
+            return;
+        };
+        if let Some(at) = task.next_wakeup_in(now, &self.blackouts, |n| self.tasks.get(n)) {
+            self.queue.insert((at, name.to_string()));
+            self.wakeups.insert(name.to_string(), at);
+        }
+    }
+
+    fn schedule_dependents(&mut self, name: &str, now: DateTime<Local>) {
+        let dependents: Vec<String> = self
+            .dependents
+            .get(name)
+            .map(|names| names.iter().cloned().collect())
+            .unwrap_or_default();
+        for dependent in dependents {
+            self.schedule(&dependent, now);
+        }
+    }
+
+    fn unqueue(&mut self, name: &str) {
+        if let Some(at) = self.wakeups.remove(name) {
+            self.queue.remove(&(at, name.to_string()));
+        }
+    }
+
+    // Drops `task` from the dependents of upstream tasks it no longer runs after
+    fn forget_dependencies(&mut self, task: &Task) {
+        let current = self.tasks.get(&task.name);
+        for upstream in &task.depends_on {
+            if current.is_some_and(|t| t.depends_on.contains(upstream)) {
+                continue;
+            }
+            if let Some(names) = self.dependents.get_mut(upstream) {
+                names.remove(&task.name);
+                if names.is_empty() {
+                    self.dependents.remove(upstream);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Interval;
+    use chrono::{Duration, TimeZone};
+
+    fn at(h: u32, m: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 5, 8, h, m, 0).unwrap()
+    }
+
+    fn every_hour(name: &str, last_run: DateTime<Local>) -> Task {
+        let mut task = Task::new(name, Interval::Hourly);
+        task.enabled = true;
+        task.last_run = Some(last_run);
+        task
+    }
+
+    #[test]
+    fn test_poll_reports_due_tasks_until_updated() {
+        let mut scheduler = Scheduler::with_tasks(
+            [every_hour("B", at(8, 0)), every_hour("A", at(8, 30))],
+            vec![],
+            at(8, 45),
+        );
+        assert_eq!(scheduler.next_wakeup(), Some(at(9, 0)));
+        assert!(scheduler.poll(at(8, 59)).is_empty());
+
+        let events = scheduler.poll(at(9, 40));
+        let names: Vec<&str> = events.iter().map(|e| e.task.as_str()).collect();
+        assert_eq!(names, vec!["B", "A"]);
+        assert_eq!(events[0].due.run, vec![at(9, 0)]);
+        assert_eq!(scheduler.next_wakeup(), None, "Both wait to be handed back");
+
+        let mut b = scheduler.get("B").unwrap().clone();
+        b.mark_complete(at(9, 40));
+        scheduler.update(b, at(9, 40));
+        assert_eq!(scheduler.next_wakeup(), Some(at(10, 40)));
+
+        scheduler.remove("B", at(9, 40));
+        assert_eq!(scheduler.next_wakeup(), None);
+        assert_eq!(scheduler.len(), 1);
+    }
+
+    #[test]
+    fn test_upstream_update_releases_dependents() {
+        let mut load = every_hour("Load", at(8, 30));
+        load.depends_on = vec!["Extract".into()];
+        let mut scheduler =
+            Scheduler::with_tasks([load, every_hour("Extract", at(8, 0))], vec![], at(9, 0));
+
+        let events = scheduler.poll(at(9, 30));
+        let names: Vec<&str> = events.iter().map(|e| e.task.as_str()).collect();
+        assert_eq!(names, vec!["Extract"], "Load waits for Extract");
+
+        let mut extract = scheduler.get("Extract").unwrap().clone();
+        extract.mark_complete(at(9, 30));
+        scheduler.update(extract, at(9, 30));
+        assert_eq!(scheduler.next_wakeup(), Some(at(9, 30)));
+        let events = scheduler.poll(at(9, 30));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].task, "Load");
+    }
+
+    #[test]
+    fn test_dependents_wait_for_upstream_run_to_finish() {
+        let mut load = every_hour("Load", at(8, 30));
+        load.depends_on = vec!["Extract".into()];
+        let mut extract = every_hour("Extract", at(8, 0));
+        extract.action = crate::Action::from_command_line("extract.sh").unwrap();
+        extract.last_success = Some(at(8, 5));
+        let mut scheduler = Scheduler::with_tasks([load, extract], vec![], at(9, 0));
+        assert_eq!(scheduler.poll(at(9, 30))[0].task, "Extract");
+
+        // The run has started but not finished
+        let mut extract = scheduler.get("Extract").unwrap().clone();
+        extract.mark_complete(at(9, 30));
+        scheduler.update(extract.clone(), at(9, 30));
+        assert!(
+            scheduler.poll(at(9, 31)).is_empty(),
+            "Load waits while Extract runs"
+        );
+
+        let finished = |status| crate::RunRecord {
+            started: at(9, 30),
+            finished: at(9, 40),
+            status,
+            stdout: None,
+            stderr: None,
+            output_error: None,
+        };
+        extract.record_outcome(finished(crate::RunStatus::Exited(1)));
+        scheduler.update(extract.clone(), at(9, 40));
+        assert!(
+            scheduler.poll(at(9, 41)).is_empty(),
+            "a failed run does not release Load"
+        );
+
+        extract.record_outcome(finished(crate::RunStatus::Exited(0)));
+        scheduler.update(extract, at(9, 40));
+        let events = scheduler.poll(at(9, 41));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].task, "Load");
+    }
+
+    #[test]
+    fn test_blackout_keeps_task_queued() {
+        let quiet = crate::parse_blackouts("09:00-10:00").unwrap();
+        let mut scheduler =
+            Scheduler::with_tasks([every_hour("Sync", at(8, 15))], vec![], at(8, 30));
+        assert_eq!(scheduler.next_wakeup(), Some(at(9, 15)));
+
+        scheduler.set_blackouts(quiet, at(8, 30));
+        assert_eq!(scheduler.next_wakeup(), Some(at(10, 0)));
+        let events = scheduler.poll(at(10, 0));
+        assert_eq!(events[0].due.run, vec![at(9, 15)]);
+    }
+
+    #[test]
+    fn test_scales_to_many_tasks() {
+        let start = at(0, 0);
+        // 30,000 hourly tasks whose last runs are spread over an hour
+        let tasks = (0..30_000).map(|i| {
+            every_hour(
+                &format!("task-{}", i),
+                start - Duration::hours(1) + Duration::milliseconds(i * 120),
+            )
+        });
+        let mut scheduler = Scheduler::with_tasks(tasks, vec![], start);
+        assert_eq!(scheduler.len(), 30_000);
+        assert_eq!(scheduler.next_wakeup(), Some(start));
+
+        // One minute's worth falls due
+        let events = scheduler.poll(start + Duration::milliseconds(59_999));
+        assert_eq!(events.len(), 500);
+        assert_eq!(
+            scheduler.next_wakeup(),
+            Some(start + Duration::milliseconds(60_000))
+        );
+    }
+}
@@ -0,0 +1,51 @@
+use anyhow::{anyhow, Context};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::{project_file_path, BlackoutWindow, BusinessCalendar};
+
+/// App-wide scheduler settings, stored in settings.json next to tasks.json.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// When set, tasks that fall due on the same check are started at evenly
+    /// spaced offsets across this window instead of all at once.
+    pub spread_window: Option<Duration>,
+    /// Windows during which no task starts, evaluated in each task's time zone.
+    pub blackouts: Vec<BlackoutWindow>,
+    /// Business calendars that tasks can reference by name.
+    pub calendars: Vec<BusinessCalendar>,
+    /// Most runs in progress at once; further due tasks queue by priority. `None`
+    /// is unlimited.
+    pub max_concurrent: Option<usize>,
+}
+
+/// `settings.json`, next to `tasks.json`.
+pub fn settings_file_path() -> Option<PathBuf> {
+    project_file_path().map(|path| path.with_file_name("settings.json"))
+}
+
+pub async fn load_settings() -> Result<Settings, anyhow::Error> {
+    let path = settings_file_path().ok_or_else(|| anyhow!("no config dir"))?;
+    if !path.exists() {
+        return Ok(Settings::default());
+    }
+
+    let data = fs::read_to_string(&path)
+        .with_context(|| format!("reading settings file {}", path.display()))?;
+    serde_json::from_str(&data).with_context(|| format!("parsing settings file {}", path.display()))
+}
+
+pub async fn save_settings(settings: &Settings) -> Result<(), anyhow::Error> {
+    let path = settings_file_path().ok_or_else(|| anyhow!("no config dir"))?;
+    let json = serde_json::to_string_pretty(settings).context("serializing settings")?;
+
+    // Write atomically: write to temp file and then rename
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, &json).with_context(|| format!("writing temp file {}", tmp.display()))?;
+    fs::rename(&tmp, &path)
+        .with_context(|| format!("renaming {} -> {}", tmp.display(), path.display()))?;
+    Ok(())
+}
@@ -0,0 +1,184 @@
+use chrono::{DateTime, Local};
+
+use crate::{BlackoutWindow, Scheduler, Task};
+
+// Bounds the work done for a horizon far in the future or a very frequent task
+const MAX_SIMULATED_STEPS: usize = 100_000;
+
+/// One run found by [`simulate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulatedRun {
+    pub task: String,
+    /// When the run would start.
+    pub at: DateTime<Local>,
+    /// The occurrence it runs for. Earlier than `at` for catch-up runs and runs
+    /// held back by a blackout window or an upstream task.
+    pub scheduled: DateTime<Local>,
+}
+
+/// The result of [`simulate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Simulation {
+    /// Every run found, in start order.
+    pub runs: Vec<SimulatedRun>,
+    /// Set when the simulation gave up before reaching its end time, e.g. for very
+    /// frequent tasks over a long range. Runs after this time are missing.
+    pub stopped_at: Option<DateTime<Local>>,
+}
+
+/// Dry-runs the scheduler from `from` to `until` without touching the tasks, and
+/// returns every run that would happen in start order.
+///
+/// Tasks are checked the moment they fall due, honoring each task's interval,
+/// enabled flag, last run, misfire policy, validity window, snooze, dependencies and
+/// the task and global blackout windows. Random start delays, spreading and the
+/// concurrency limit are not simulated.
+pub fn simulate(
+    tasks: &[Task],
+    from: DateTime<Local>,
+    until: DateTime<Local>,
+    global_blackouts: &[BlackoutWindow],
+) -> Simulation {
+    simulate_steps(tasks, from, until, global_blackouts, MAX_SIMULATED_STEPS)
+}
+
+fn simulate_steps(
+    tasks: &[Task],
+    from: DateTime<Local>,
+    until: DateTime<Local>,
+    global_blackouts: &[BlackoutWindow],
+    max_steps: usize,
+) -> Simulation {
+    let mut scheduler =
+        Scheduler::with_tasks(tasks.iter().cloned(), global_blackouts.to_vec(), from);
+    let mut runs = Vec::new();
+    let mut now = from;
+
+    for _ in 0..max_steps {
+        let Some(at) = scheduler.next_wakeup().filter(|at| *at <= until) else {
+            return Simulation {
+                runs,
+                stopped_at: None,
+            };
+        };
+        now = at.max(now);
+        for event in scheduler.poll(now) {
+            if event.due.deferred.is_some() {
+                continue;
+            }
+            let Some(mut task) = scheduler.get(&event.task).cloned() else {
+                continue;
+            };
+            for scheduled in &event.due.run {
+                runs.push(SimulatedRun {
+                    task: task.name.clone(),
+                    at: now,
+                    scheduled: *scheduled,
+                });
+                task.mark_complete(now);
+                // Nothing runs, so assume each command succeeds and releases the
+                // tasks that run after it
+                if task.action.is_some() {
+                    task.last_success = Some(now);
+                }
+            }
+            if let (true, Some(last)) = (event.due.run.is_empty(), event.due.skipped.last()) {
+                task.mark_skipped(*last);
+            }
+            scheduler.update(task, now);
+        }
+    }
+    // Out of steps; complete only if nothing else falls due in range
+    let stopped_at = scheduler
+        .next_wakeup()
+        .filter(|at| *at <= until)
+        .map(|_| now);
+    Simulation { runs, stopped_at }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Interval, MisfirePolicy};
+    use chrono::{NaiveTime, TimeZone};
+
+    fn at(d: u32, h: u32, m: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 5, d, h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_simulate_lists_runs_in_order() {
+        let mut hourly = Task::new(
+            "Hourly",
+            Interval::Every(std::time::Duration::from_secs(3600)),
+        );
+        hourly.enabled = true;
+        hourly.last_run = Some(at(6, 8, 30));
+        let mut report = Task::new(
+            "Report",
+            Interval::At {
+                time: NaiveTime::from_hms_opt(10, 0, 0).unwrap(),
+                weekdays: vec![],
+            },
+        );
+        report.enabled = true;
+        report.last_run = Some(at(5, 10, 0));
+        report.misfire = MisfirePolicy::Skip;
+        let disabled = Task::new("Disabled", Interval::Hourly);
+
+        let runs = simulate(&[hourly, report, disabled], at(6, 9, 0), at(6, 11, 0), &[]).runs;
+        let summary: Vec<(&str, DateTime<Local>)> =
+            runs.iter().map(|r| (r.task.as_str(), r.at)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Hourly", at(6, 9, 30)),
+                ("Report", at(6, 10, 0)),
+                ("Hourly", at(6, 10, 30)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_simulate_honors_blackouts_and_dependencies() {
+        let mut extract = Task::new("Extract", Interval::Daily);
+        extract.enabled = true;
+        extract.last_run = Some(at(6, 1, 0));
+        let mut load = Task::new("Load", Interval::Hourly);
+        load.enabled = true;
+        load.last_run = Some(at(6, 1, 30));
+        load.depends_on = vec!["Extract".into()];
+        let quiet = crate::parse_blackouts("00:00-06:00").unwrap();
+
+        let runs = simulate(&[extract, load], at(6, 12, 0), at(7, 12, 0), &quiet).runs;
+        // Extract is due at 01:00 but waits for the quiet hours, and Load only
+        // runs once Extract has
+        let summary: Vec<(&str, DateTime<Local>, DateTime<Local>)> = runs
+            .iter()
+            .map(|r| (r.task.as_str(), r.at, r.scheduled))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Extract", at(7, 6, 0), at(7, 1, 0)),
+                ("Load", at(7, 6, 0), at(7, 5, 30)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_simulate_reports_where_it_stopped() {
+        let mut ticker = Task::new("Ticker", Interval::Every(std::time::Duration::from_secs(1)));
+        ticker.enabled = true;
+        ticker.last_run = Some(at(6, 9, 0));
+        let tasks = [ticker];
+
+        let partial = simulate_steps(&tasks, at(6, 9, 0), at(6, 10, 0), &[], 100);
+        assert_eq!(partial.runs.len(), 100);
+        assert_eq!(partial.stopped_at, Some(partial.runs[99].at));
+
+        let full = simulate_steps(&tasks, at(6, 9, 0), at(6, 9, 1), &[], 100);
+        assert_eq!(full.runs.len(), 60);
+        assert_eq!(full.stopped_at, None);
+    }
+}
@@ -1,5 +1,9 @@
 use anyhow::Result;
-use iced_task_scheduler::{load_tasks, save_tasks, Interval, Task};
+use chrono::Local;
+use iced_task_scheduler::{
+    load_tasks, project_file_path, replace_tasks, run_output_dir, save_tasks, settings_file_path,
+    Interval, Task,
+};
 use serial_test::serial;
 use std::env;
 use std::fs;
@@ -118,3 +122,110 @@ fn test_concurrent_modifications() {
         }
     });
 }
+
+// One-shot tasks keep their target time and completed state across a save/load cycle
+#[serial]
+#[test]
+fn test_one_shot_task_persists() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async {
+        let _temp_dir = setup_test_env().await.unwrap();
+        save_tasks(&[]).await.unwrap();
+
+        let at = Local::now() - chrono::Duration::minutes(1);
+        let mut pending = Task::new("Pending", Interval::Once(at));
+        pending.enabled = true;
+        let mut done = Task::new("Done", Interval::Once(at));
+        done.enabled = true;
+        done.mark_complete(at);
+
+        save_tasks(&[pending, done]).await.unwrap();
+
+        let loaded = load_tasks().await.unwrap();
+        let pending = loaded.iter().find(|t| t.name == "Pending").unwrap();
+        let done = loaded.iter().find(|t| t.name == "Done").unwrap();
+
+        assert!(pending.should_run(Local::now()));
+        assert!(done.is_completed());
+        assert!(!done.enabled);
+        assert!(!done.should_run(Local::now()));
+    });
+}
+
+// Dependency cycles are rejected at save time and leave the stored tasks untouched
+#[serial]
+#[test]
+fn test_save_rejects_dependency_cycle() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async {
+        let _temp_dir = setup_test_env().await.unwrap();
+        save_tasks(&[]).await.unwrap();
+
+        let extract = Task::new("Extract", Interval::Daily);
+        let mut load = Task::new("Load", Interval::Daily);
+        load.depends_on = vec!["Extract".into()];
+        save_tasks(&[extract.clone(), load.clone()]).await.unwrap();
+
+        let mut extract = extract;
+        extract.depends_on = vec!["Load".into()];
+        let err = save_tasks(&[extract, load]).await.unwrap_err();
+        assert!(err.to_string().contains("dependency cycle"));
+
+        let loaded = load_tasks().await.unwrap();
+        let extract = loaded.iter().find(|t| t.name == "Extract").unwrap();
+        assert!(extract.depends_on.is_empty());
+    });
+}
+
+// Replacing the task list drops removed tasks, and overlapping saves each write
+// through their own temp file
+#[serial]
+#[test]
+fn test_replace_tasks_overlapping_saves() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async {
+        let _temp_dir = setup_test_env().await.unwrap();
+        save_tasks(&[]).await.unwrap();
+
+        let backup = Task::new("Backup", Interval::Daily);
+        let report = Task::new("Report", Interval::Weekly);
+        replace_tasks(&[backup.clone(), report]).await.unwrap();
+        replace_tasks(std::slice::from_ref(&backup)).await.unwrap();
+        let names: Vec<String> = load_tasks()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(names, vec!["Backup"]);
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let tasks = vec![backup.clone()];
+                tokio::spawn(async move { replace_tasks(&tasks).await })
+            })
+            .collect();
+        for handle in handles {
+            handle.await.unwrap().unwrap();
+        }
+        assert_eq!(load_tasks().await.unwrap().len(), 1);
+
+        let dup = replace_tasks(&[backup.clone(), backup]).await.unwrap_err();
+        assert!(dup.to_string().contains("more than one task"));
+    });
+}
+
+// The config dir override moves the tasks, the settings and the run output together
+#[serial]
+#[test]
+fn test_config_override_keeps_files_together() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async {
+        let temp_dir = setup_test_env().await.unwrap();
+        let dir = temp_dir.path().join("config_override");
+
+        assert_eq!(project_file_path(), Some(dir.join("tasks.json")));
+        assert_eq!(settings_file_path(), Some(dir.join("settings.json")));
+        assert_eq!(run_output_dir(), Some(dir.join("runs")));
+    });
+}